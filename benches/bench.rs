@@ -12,7 +12,7 @@ type FnvBuilder = BuildHasherDefault<FnvHasher>;
 use test::black_box;
 use test::Bencher;
 
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 
 use std::collections::HashMap;
 
@@ -761,3 +761,65 @@ fn indexmap_clone_for_sort_u32(b: &mut Bencher) {
 
     b.iter(|| map.clone());
 }
+
+#[bench]
+fn map_values_indexmap_100_000(b: &mut Bencher) {
+    let map = IMAP_100K.clone();
+
+    b.iter(|| map.clone().map_values(|_, value| u64::from(value)));
+}
+
+#[bench]
+fn collect_mapped_values_indexmap_100_000(b: &mut Bencher) {
+    let map = IMAP_100K.clone();
+
+    b.iter(|| {
+        map.clone()
+            .into_iter()
+            .map(|(key, value)| (key, u64::from(value)))
+            .collect::<IndexMap<_, _>>()
+    });
+}
+
+#[bench]
+fn into_key_set_indexmap_100_000(b: &mut Bencher) {
+    let map = IMAP_100K.clone();
+
+    b.iter(|| map.clone().into_key_set());
+}
+
+#[bench]
+fn collect_key_set_indexmap_100_000(b: &mut Bencher) {
+    let map = IMAP_100K.clone();
+
+    b.iter(|| map.clone().into_keys().collect::<IndexSet<_>>());
+}
+
+fn split_indexmap_100_000() -> (IndexMap<u32, u32>, IndexMap<u32, u32>) {
+    let mut first = IMAP_100K.clone();
+    let mut second = IndexMap::with_hasher(first.hasher().clone());
+    second.extend(first.drain(50_000..));
+    (first, second)
+}
+
+#[bench]
+fn append_indexmap_100_000(b: &mut Bencher) {
+    let (first, second) = split_indexmap_100_000();
+
+    b.iter(|| {
+        let (mut map, mut other) = (first.clone(), second.clone());
+        map.append(&mut other);
+        map
+    });
+}
+
+#[bench]
+fn append_with_hashes_indexmap_100_000(b: &mut Bencher) {
+    let (first, second) = split_indexmap_100_000();
+
+    b.iter(|| {
+        let (mut map, mut other) = (first.clone(), second.clone());
+        map.append_with_hashes(&mut other);
+        map
+    });
+}
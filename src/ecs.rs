@@ -0,0 +1,18 @@
+//! Integration of [`IndexMap`][crate::IndexMap] and [`IndexSet`][crate::IndexSet] with
+//! [`bevy_ecs`].
+
+pub mod map;
+pub mod observed;
+pub mod registry;
+mod relationship;
+pub mod set;
+pub mod slice;
+pub mod tracked;
+
+pub use self::map::EntityIndexMap;
+pub use self::observed::ObservedIndexMap;
+pub use self::registry::OrderedRegistry;
+pub use self::relationship::OrderedRelated;
+pub use self::set::EntityIndexSet;
+pub use self::slice::UniqueEntityIndexSlice;
+pub use self::tracked::TrackedIndexMap;
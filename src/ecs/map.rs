@@ -0,0 +1,623 @@
+//! [`EntityIndexMap`] is an [`IndexMap`] keyed by entities that `bevy_ecs` knows to be unique.
+
+use super::UniqueEntityIndexSlice;
+use crate::map::{self, IndexMap};
+use crate::ReplaceKeyError;
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{Deref, Index, RangeBounds};
+
+use bevy_ecs::entity::{
+    Entity, EntityEquivalent, EntityHash, EntityMapper, EntitySetIterator, MapEntities,
+};
+
+/// An [`IndexMap`] pre-configured to use [`EntityHash`] hashing.
+///
+/// This is the ordered counterpart of `bevy_ecs`'s `EntityHashMap`: the iterators over
+/// its keys are known to yield unique entities, so they can be passed directly to APIs
+/// like `Query::iter_many_unique_mut`. Read-only operations are available through
+/// [`Deref`] to the inner [`IndexMap`], and the mutating ones are forwarded by methods
+/// of the same names. There is no `DerefMut`, since the inner map could then be used
+/// with [`MutableKeys`][crate::map::MutableKeys], which can make two keys equal. For
+/// the same reason, the entry API isn't forwarded, because of
+/// [`MutableEntryKey`][crate::map::MutableEntryKey].
+///
+/// # Examples
+///
+/// ```
+/// use bevy_ecs::entity::{Entity, EntitySetIterator};
+/// use indexmap::ecs::{EntityIndexMap, EntityIndexSet};
+///
+/// let a = Entity::from_raw(1);
+/// let b = Entity::from_raw(2);
+/// let map: EntityIndexMap<&str> = [(b, "b"), (a, "a")].into();
+/// assert_eq!(map[&a], "a");
+///
+/// // The uniqueness of the keys is known to `bevy_ecs`.
+/// let keys: EntityIndexSet = map.keys().copied().collect_set();
+/// assert!(keys.iter().eq(&[b, a]));
+/// ```
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityIndexMap<V>(pub(crate) IndexMap<Entity, V, EntityHash>);
+
+impl<V> EntityIndexMap<V> {
+    /// Creates an empty `EntityIndexMap`.
+    ///
+    /// Equivalent to [`IndexMap::with_hasher(EntityHash)`][IndexMap::with_hasher].
+    pub const fn new() -> Self {
+        Self(IndexMap::with_hasher(EntityHash))
+    }
+
+    /// Creates an empty `EntityIndexMap` with the specified capacity.
+    ///
+    /// Equivalent to
+    /// [`IndexMap::with_capacity_and_hasher(n, EntityHash)`][IndexMap::with_capacity_and_hasher].
+    pub fn with_capacity(n: usize) -> Self {
+        Self(IndexMap::with_capacity_and_hasher(n, EntityHash))
+    }
+
+    /// Returns the inner [`IndexMap`].
+    pub fn into_inner(self) -> IndexMap<Entity, V, EntityHash> {
+        self.0
+    }
+
+    /// Return an iterator over the keys of the map, in their order.
+    ///
+    /// Equivalent to [`IndexMap::keys`].
+    pub fn keys(&self) -> Keys<'_, V> {
+        Keys(self.0.keys(), PhantomData)
+    }
+
+    /// Return an owning iterator over the keys of the map, in their order.
+    ///
+    /// Equivalent to [`IndexMap::into_keys`].
+    pub fn into_keys(self) -> IntoKeys<V> {
+        IntoKeys(self.0.into_keys(), PhantomData)
+    }
+
+    /// Returns a slice of all the keys in the map.
+    ///
+    /// Equivalent to [`IndexMap::as_unique_entity_slice`], without its safety requirements.
+    pub fn as_unique_entity_slice(&self) -> &UniqueEntityIndexSlice<V> {
+        #[allow(unsafe_code)]
+        // SAFETY: `EntityIndexMap` never gives mutable access to its inner map, so its
+        // keys can't have been made equal through `MutableKeys` or `MutableEntryKey`.
+        unsafe {
+            self.0.as_unique_entity_slice()
+        }
+    }
+
+    /// Returns a slice of the keys in the given range of indices.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    ///
+    /// Equivalent to [`IndexMap::get_unique_entity_range`], without its safety requirements.
+    pub fn get_unique_entity_range<R: RangeBounds<usize>>(
+        &self,
+        range: R,
+    ) -> Option<&UniqueEntityIndexSlice<V>> {
+        self.as_unique_entity_slice().get_range(range)
+    }
+
+    /// Return an iterator over mutable references to the values of the map, in their order.
+    ///
+    /// Equivalent to [`IndexMap::values_mut`].
+    pub fn values_mut(&mut self) -> map::ValuesMut<'_, Entity, V> {
+        self.0.values_mut()
+    }
+
+    /// Return an iterator over the entries of the map, with mutable references to
+    /// the values, in their order.
+    ///
+    /// Equivalent to [`IndexMap::iter_mut`].
+    pub fn iter_mut(&mut self) -> map::IterMut<'_, Entity, V> {
+        self.0.iter_mut()
+    }
+
+    /// Return a mutable reference to the value stored for `key`, if it is present.
+    ///
+    /// Equivalent to [`IndexMap::get_mut`].
+    pub fn get_mut(&mut self, key: &Entity) -> Option<&mut V> {
+        self.0.get_mut(key)
+    }
+
+    /// Return the index, key and a mutable reference to the value stored for `key`,
+    /// if it is present.
+    ///
+    /// Equivalent to [`IndexMap::get_full_mut`].
+    pub fn get_full_mut(&mut self, key: &Entity) -> Option<(usize, &Entity, &mut V)> {
+        self.0.get_full_mut(key)
+    }
+
+    /// Get a key and a mutable reference to its value by index.
+    ///
+    /// Equivalent to [`IndexMap::get_index_mut`].
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&Entity, &mut V)> {
+        self.0.get_index_mut(index)
+    }
+
+    /// Insert a key-value pair in the map, and return the old value if the key was
+    /// already present.
+    ///
+    /// Equivalent to [`IndexMap::insert`].
+    pub fn insert(&mut self, key: Entity, value: V) -> Option<V> {
+        self.0.insert(key, value)
+    }
+
+    /// Insert a key-value pair in the map, and return its index and the old value if
+    /// the key was already present.
+    ///
+    /// Equivalent to [`IndexMap::insert_full`].
+    pub fn insert_full(&mut self, key: Entity, value: V) -> (usize, Option<V>) {
+        self.0.insert_full(key, value)
+    }
+
+    /// Insert a key-value pair in the map at its ordered position among sorted keys.
+    ///
+    /// Equivalent to [`IndexMap::insert_sorted`].
+    pub fn insert_sorted(&mut self, key: Entity, value: V) -> (usize, Option<V>) {
+        self.0.insert_sorted(key, value)
+    }
+
+    /// Insert a key-value pair in the map before the entry at the given index, or at
+    /// the end.
+    ///
+    /// Equivalent to [`IndexMap::insert_before`].
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    #[track_caller]
+    pub fn insert_before(&mut self, index: usize, key: Entity, value: V) -> (usize, Option<V>) {
+        self.0.insert_before(index, key, value)
+    }
+
+    /// Insert a key-value pair in the map at the given index.
+    ///
+    /// Equivalent to [`IndexMap::shift_insert`].
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    #[track_caller]
+    pub fn shift_insert(&mut self, index: usize, key: Entity, value: V) -> Option<V> {
+        self.0.shift_insert(index, key, value)
+    }
+
+    /// Replace the key of the entry for `old` with `new`, keeping its value and its
+    /// position, and return its index.
+    ///
+    /// Equivalent to [`IndexMap::replace_key`].
+    pub fn replace_key(
+        &mut self,
+        old: &Entity,
+        new: Entity,
+    ) -> Result<usize, ReplaceKeyError<Entity>> {
+        self.0.replace_key(old, new)
+    }
+
+    /// Remove the key-value pair for `key` by swapping it with the last element, and
+    /// return its value if it was present.
+    ///
+    /// Equivalent to [`IndexMap::swap_remove`].
+    pub fn swap_remove(&mut self, key: &Entity) -> Option<V> {
+        self.0.swap_remove(key)
+    }
+
+    /// Remove the key-value pair for `key` by shifting all of the elements that follow
+    /// it, and return its value if it was present.
+    ///
+    /// Equivalent to [`IndexMap::shift_remove`].
+    pub fn shift_remove(&mut self, key: &Entity) -> Option<V> {
+        self.0.shift_remove(key)
+    }
+
+    /// Remove the key-value pair for `key` by swapping it with the last element, and
+    /// return it if it was present.
+    ///
+    /// Equivalent to [`IndexMap::swap_remove_entry`].
+    pub fn swap_remove_entry(&mut self, key: &Entity) -> Option<(Entity, V)> {
+        self.0.swap_remove_entry(key)
+    }
+
+    /// Remove the key-value pair for `key` by shifting all of the elements that follow
+    /// it, and return it if it was present.
+    ///
+    /// Equivalent to [`IndexMap::shift_remove_entry`].
+    pub fn shift_remove_entry(&mut self, key: &Entity) -> Option<(Entity, V)> {
+        self.0.shift_remove_entry(key)
+    }
+
+    /// Remove the key-value pair for `key` by swapping it with the last element, and
+    /// return its index, key and value if it was present.
+    ///
+    /// Equivalent to [`IndexMap::swap_remove_full`].
+    pub fn swap_remove_full(&mut self, key: &Entity) -> Option<(usize, Entity, V)> {
+        self.0.swap_remove_full(key)
+    }
+
+    /// Remove the key-value pair for `key` by shifting all of the elements that follow
+    /// it, and return its index, key and value if it was present.
+    ///
+    /// Equivalent to [`IndexMap::shift_remove_full`].
+    pub fn shift_remove_full(&mut self, key: &Entity) -> Option<(usize, Entity, V)> {
+        self.0.shift_remove_full(key)
+    }
+
+    /// Remove the key-value pair at the given index by swapping it with the last element.
+    ///
+    /// Equivalent to [`IndexMap::swap_remove_index`].
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(Entity, V)> {
+        self.0.swap_remove_index(index)
+    }
+
+    /// Remove the key-value pair at the given index by shifting all of the elements
+    /// that follow it.
+    ///
+    /// Equivalent to [`IndexMap::shift_remove_index`].
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(Entity, V)> {
+        self.0.shift_remove_index(index)
+    }
+
+    /// Remove the last key-value pair.
+    ///
+    /// Equivalent to [`IndexMap::pop`].
+    pub fn pop(&mut self) -> Option<(Entity, V)> {
+        self.0.pop()
+    }
+
+    /// Keep only the key-value pairs for which `keep` returns `true`, in their order.
+    ///
+    /// Equivalent to [`IndexMap::retain`].
+    pub fn retain<F>(&mut self, keep: F)
+    where
+        F: FnMut(&Entity, &mut V) -> bool,
+    {
+        self.0.retain(keep);
+    }
+
+    /// Remove all key-value pairs in the map, while preserving its capacity.
+    ///
+    /// Equivalent to [`IndexMap::clear`].
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Shortens the map, keeping the first `len` elements and dropping the rest.
+    ///
+    /// Equivalent to [`IndexMap::truncate`].
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    /// Remove the key-value pairs in the given index range from the map, and return
+    /// them in an iterator.
+    ///
+    /// Equivalent to [`IndexMap::drain`].
+    ///
+    /// ***Panics*** if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the map.
+    #[track_caller]
+    pub fn drain<R>(&mut self, range: R) -> map::Drain<'_, Entity, V>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.0.drain(range)
+    }
+
+    /// Splits the map into two at the given index, and returns the elements from
+    /// `at` on.
+    ///
+    /// Equivalent to [`IndexMap::split_off`].
+    ///
+    /// ***Panics*** if `at > len`.
+    #[track_caller]
+    pub fn split_off(&mut self, at: usize) -> Self {
+        Self(self.0.split_off(at))
+    }
+
+    /// Moves all key-value pairs from `other` into `self`, leaving `other` empty.
+    ///
+    /// Equivalent to [`IndexMap::append`].
+    pub fn append(&mut self, other: &mut Self) {
+        self.0.append(&mut other.0);
+    }
+
+    /// Reserve capacity for `additional` more key-value pairs.
+    ///
+    /// Equivalent to [`IndexMap::reserve`].
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    /// Shrink the capacity of the map as much as possible.
+    ///
+    /// Equivalent to [`IndexMap::shrink_to_fit`].
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+    }
+
+    /// Sort the key-value pairs of the map by their keys.
+    ///
+    /// Equivalent to [`IndexMap::sort_keys`].
+    pub fn sort_keys(&mut self) {
+        self.0.sort_keys();
+    }
+
+    /// Sort the key-value pairs of the map in place using the comparison function `cmp`.
+    ///
+    /// Equivalent to [`IndexMap::sort_by`].
+    pub fn sort_by<F>(&mut self, cmp: F)
+    where
+        F: FnMut(&Entity, &V, &Entity, &V) -> Ordering,
+    {
+        self.0.sort_by(cmp);
+    }
+
+    /// Sort the key-value pairs of the map by their keys, without preserving the order
+    /// of equal ones.
+    ///
+    /// Equivalent to [`IndexMap::sort_unstable_keys`].
+    pub fn sort_unstable_keys(&mut self) {
+        self.0.sort_unstable_keys();
+    }
+
+    /// Sort the key-value pairs of the map in place using the comparison function `cmp`,
+    /// without preserving the order of equal ones.
+    ///
+    /// Equivalent to [`IndexMap::sort_unstable_by`].
+    pub fn sort_unstable_by<F>(&mut self, cmp: F)
+    where
+        F: FnMut(&Entity, &V, &Entity, &V) -> Ordering,
+    {
+        self.0.sort_unstable_by(cmp);
+    }
+
+    /// Sort the key-value pairs of the map in place using a key extraction function,
+    /// calling it only once per element.
+    ///
+    /// Equivalent to [`IndexMap::sort_by_cached_key`].
+    pub fn sort_by_cached_key<T, F>(&mut self, sort_key: F)
+    where
+        T: Ord,
+        F: FnMut(&Entity, &V) -> T,
+    {
+        self.0.sort_by_cached_key(sort_key);
+    }
+
+    /// Reverses the order of the key-value pairs in place.
+    ///
+    /// Equivalent to [`IndexMap::reverse`].
+    pub fn reverse(&mut self) {
+        self.0.reverse();
+    }
+
+    /// Moves the position of a key-value pair from one index to another by shifting
+    /// all other pairs in-between.
+    ///
+    /// Equivalent to [`IndexMap::move_index`].
+    ///
+    /// ***Panics*** if `from` or `to` are out of bounds.
+    #[track_caller]
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.0.move_index(from, to);
+    }
+
+    /// Swaps the position of two key-value pairs in the map.
+    ///
+    /// Equivalent to [`IndexMap::swap_indices`].
+    ///
+    /// ***Panics*** if `a` or `b` are out of bounds.
+    #[track_caller]
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.0.swap_indices(a, b);
+    }
+}
+
+impl<V> Default for EntityIndexMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Deref for EntityIndexMap<V> {
+    type Target = IndexMap<Entity, V, EntityHash>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, V: Copy> Extend<(&'a Entity, &'a V)> for EntityIndexMap<V> {
+    fn extend<T: IntoIterator<Item = (&'a Entity, &'a V)>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl<V> Extend<(Entity, V)> for EntityIndexMap<V> {
+    fn extend<T: IntoIterator<Item = (Entity, V)>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl<V, const N: usize> From<[(Entity, V); N]> for EntityIndexMap<V> {
+    fn from(arr: [(Entity, V); N]) -> Self {
+        Self(IndexMap::from_iter(arr))
+    }
+}
+
+impl<V> FromIterator<(Entity, V)> for EntityIndexMap<V> {
+    fn from_iter<I: IntoIterator<Item = (Entity, V)>>(iterable: I) -> Self {
+        Self(IndexMap::from_iter(iterable))
+    }
+}
+
+impl<V, Q: EntityEquivalent + ?Sized> Index<&Q> for EntityIndexMap<V> {
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        &self.0[&key.entity()]
+    }
+}
+
+impl<V: MapEntities> MapEntities for EntityIndexMap<V> {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        self.0.map_entities(entity_mapper);
+    }
+}
+
+impl<'a, V> IntoIterator for &'a EntityIndexMap<V> {
+    type Item = (&'a Entity, &'a V);
+    type IntoIter = map::Iter<'a, Entity, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut EntityIndexMap<V> {
+    type Item = (&'a Entity, &'a mut V);
+    type IntoIter = map::IterMut<'a, Entity, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl<V> IntoIterator for EntityIndexMap<V> {
+    type Item = (Entity, V);
+    type IntoIter = map::IntoIter<Entity, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// An iterator over the keys of an [`EntityIndexMap`].
+///
+/// This `struct` is created by the [`EntityIndexMap::keys`] method.
+/// See its documentation for more.
+pub struct Keys<'a, V, S = EntityHash>(map::Keys<'a, Entity, V>, PhantomData<S>);
+
+impl<'a, V> Keys<'a, V> {
+    /// Returns the inner [`Keys`](map::Keys).
+    pub fn into_inner(self) -> map::Keys<'a, Entity, V> {
+        self.0
+    }
+}
+
+impl<'a, V> Deref for Keys<'a, V> {
+    type Target = map::Keys<'a, Entity, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, V> Iterator for Keys<'a, V> {
+    type Item = &'a Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<V> DoubleEndedIterator for Keys<'_, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<V> ExactSizeIterator for Keys<'_, V> {}
+
+impl<V> FusedIterator for Keys<'_, V> {}
+
+impl<V> Clone for Keys<'_, V> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for Keys<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Keys").field(&self.0).field(&self.1).finish()
+    }
+}
+
+impl<V> Default for Keys<'_, V> {
+    fn default() -> Self {
+        Self(Default::default(), PhantomData)
+    }
+}
+
+// SAFETY: Keys stems from an `EntityIndexMap`, whose entities are unique since it never
+// hands out mutable access to its inner `IndexMap<Entity, V, EntityHash>`.
+#[allow(unsafe_code)]
+unsafe impl<V> EntitySetIterator for Keys<'_, V> {}
+
+/// An owning iterator over the keys of an [`EntityIndexMap`].
+///
+/// This `struct` is created by the [`EntityIndexMap::into_keys`] method.
+/// See its documentation for more.
+pub struct IntoKeys<V, S = EntityHash>(map::IntoKeys<Entity, V>, PhantomData<S>);
+
+impl<V> IntoKeys<V> {
+    /// Returns the inner [`IntoKeys`](map::IntoKeys).
+    pub fn into_inner(self) -> map::IntoKeys<Entity, V> {
+        self.0
+    }
+}
+
+impl<V> Deref for IntoKeys<V> {
+    type Target = map::IntoKeys<Entity, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<V> Iterator for IntoKeys<V> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<V> DoubleEndedIterator for IntoKeys<V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<V> ExactSizeIterator for IntoKeys<V> {}
+
+impl<V> FusedIterator for IntoKeys<V> {}
+
+impl<V: fmt::Debug> fmt::Debug for IntoKeys<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoKeys")
+            .field(&self.0)
+            .field(&self.1)
+            .finish()
+    }
+}
+
+impl<V> Default for IntoKeys<V> {
+    fn default() -> Self {
+        Self(Default::default(), PhantomData)
+    }
+}
+
+// SAFETY: IntoKeys stems from an `EntityIndexMap`, whose entities are unique since it never
+// hands out mutable access to its inner `IndexMap<Entity, V, EntityHash>`.
+#[allow(unsafe_code)]
+unsafe impl<V> EntitySetIterator for IntoKeys<V> {}
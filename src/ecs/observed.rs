@@ -0,0 +1,841 @@
+//! [`ObservedIndexMap`] is an [`IndexMap`] resource whose changes trigger observers.
+
+use crate::map::{self, IndexMap};
+use crate::Equivalent;
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+use core::ops::{Deref, RangeBounds};
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+
+use bevy_ecs::change_detection::DetectChangesMut;
+use bevy_ecs::event::Event;
+use bevy_ecs::resource::Resource;
+use bevy_ecs::system::{Commands, ResMut};
+
+/// An [`IndexMap`] resource that queues an event for every change of its entries, to
+/// trigger observers.
+///
+/// The map can be read through [`Deref`], but it can only be modified through the
+/// methods of the wrapper, which queue [`EntryInserted`], [`EntryRemoved`],
+/// [`EntriesRemoved`], [`EntryMoved`] and [`Reordered`] events. The queued events are triggered by
+/// [`trigger_events`][Self::trigger_events], usually from the
+/// [`trigger_index_map_events`] system, so that the observers run when the commands
+/// are applied. The events are generic over the key and value types of the map, which
+/// tells the observers of different maps apart.
+///
+/// Bulk operations are batched: [`retain`][Self::retain], [`drain`][Self::drain] and
+/// [`clear`][Self::clear] queue a single [`EntriesRemoved`] event with all the removed
+/// entries, without the moves of the entries that shift, and sorting queues a single
+/// [`Reordered`] event.
+///
+/// Changing the value of an existing entry doesn't queue any event. Bevy's resource
+/// change detection already covers it.
+///
+/// # Triggering the events
+///
+/// Nothing triggers the queued events on its own: the observers only run once
+/// [`trigger_index_map_events::<K, V, S>`][trigger_index_map_events] is added to a schedule
+/// of the world, for example with `app.add_systems(Last, trigger_index_map_events::<K, V, S>)`,
+/// or once [`trigger_events`][Self::trigger_events] is called. Until then, the events stay
+/// queued in the map.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use indexmap::ecs::observed::{trigger_index_map_events, EntryInserted, EntryMoved};
+/// use indexmap::ecs::ObservedIndexMap;
+///
+/// #[derive(Resource, Default)]
+/// struct Log(Vec<String>);
+///
+/// let mut world = World::new();
+/// world.init_resource::<Log>();
+/// world.insert_resource(ObservedIndexMap::<u32, &str>::default());
+/// world.add_observer(
+///     |trigger: Trigger<EntryInserted<u32, &str>>, mut log: ResMut<Log>| {
+///         log.0.push(format!("inserted {} at {}", trigger.key, trigger.index));
+///     },
+/// );
+/// world.add_observer(|trigger: Trigger<EntryMoved<u32, &str>>, mut log: ResMut<Log>| {
+///     log.0.push(format!("moved {} to {}", trigger.key, trigger.to));
+/// });
+///
+/// let mut schedule = Schedule::default();
+/// schedule.add_systems(trigger_index_map_events::<u32, &str, RandomState>);
+///
+/// let mut monitors = world.resource_mut::<ObservedIndexMap<u32, &str>>();
+/// monitors.insert(7, "left");
+/// monitors.insert(3, "right");
+/// monitors.move_index(1, 0);
+///
+/// schedule.run(&mut world);
+/// assert_eq!(
+///     world.resource::<Log>().0,
+///     ["inserted 7 at 0", "inserted 3 at 1", "moved 3 to 0"],
+/// );
+/// # use std::hash::RandomState;
+/// ```
+#[cfg(feature = "std")]
+#[derive(Resource)]
+pub struct ObservedIndexMap<K, V, S = RandomState> {
+    map: IndexMap<K, V, S>,
+    queued: Vec<Queued<K>>,
+}
+#[cfg(not(feature = "std"))]
+#[derive(Resource)]
+pub struct ObservedIndexMap<K, V, S> {
+    map: IndexMap<K, V, S>,
+    queued: Vec<Queued<K>>,
+}
+
+/// An event queued by an [`ObservedIndexMap`].
+#[derive(Clone)]
+enum Queued<K> {
+    Inserted { key: K, index: usize },
+    Removed { key: K, index: usize },
+    RemovedBatch { keys: Vec<(usize, K)> },
+    Moved { key: K, from: usize, to: usize },
+    Reordered,
+}
+
+/// Triggered when an entry is inserted in an [`ObservedIndexMap<K, V>`][ObservedIndexMap].
+#[derive(Event)]
+pub struct EntryInserted<K, V> {
+    /// The key of the new entry.
+    pub key: K,
+    /// The index of the new entry when it was inserted.
+    pub index: usize,
+    marker: PhantomData<fn() -> V>,
+}
+
+/// Triggered when an entry is removed from an [`ObservedIndexMap<K, V>`][ObservedIndexMap].
+#[derive(Event)]
+pub struct EntryRemoved<K, V> {
+    /// The key of the removed entry.
+    pub key: K,
+    /// The index of the entry when it was removed.
+    pub index: usize,
+    marker: PhantomData<fn() -> V>,
+}
+
+/// Triggered once when several entries are removed from an
+/// [`ObservedIndexMap<K, V>`][ObservedIndexMap] by [`retain`][ObservedIndexMap::retain],
+/// [`drain`][ObservedIndexMap::drain] or [`clear`][ObservedIndexMap::clear].
+#[derive(Event)]
+pub struct EntriesRemoved<K, V> {
+    /// The index and the key of each removed entry, in increasing order of index.
+    ///
+    /// The indices are those of the entries before any of them was removed.
+    pub keys: Vec<(usize, K)>,
+    marker: PhantomData<fn() -> V>,
+}
+
+/// Triggered when an entry of an [`ObservedIndexMap<K, V>`][ObservedIndexMap] is moved
+/// to another index, other than by the shifts of insertions and removals.
+#[derive(Event)]
+pub struct EntryMoved<K, V> {
+    /// The key of the moved entry.
+    pub key: K,
+    /// The index of the entry before the move.
+    pub from: usize,
+    /// The index of the entry after the move.
+    pub to: usize,
+    marker: PhantomData<fn() -> V>,
+}
+
+/// Triggered when all the entries of an [`ObservedIndexMap<K, V>`][ObservedIndexMap] may
+/// have moved, after it was sorted or reversed.
+#[derive(Event)]
+pub struct Reordered<K, V> {
+    marker: PhantomData<fn() -> (K, V)>,
+}
+
+macro_rules! impl_event_traits {
+    ($name:ident { $($field:ident),* }) => {
+        impl<K: Clone, V> Clone for $name<K, V> {
+            fn clone(&self) -> Self {
+                $name {
+                    $($field: self.$field.clone(),)*
+                    marker: PhantomData,
+                }
+            }
+        }
+
+        impl<K: fmt::Debug, V> fmt::Debug for $name<K, V> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    $(.field(stringify!($field), &self.$field))*
+                    .finish()
+            }
+        }
+    };
+}
+
+impl_event_traits!(EntryInserted { key, index });
+impl_event_traits!(EntryRemoved { key, index });
+impl_event_traits!(EntriesRemoved { keys });
+impl_event_traits!(EntryMoved { key, from, to });
+
+impl<K, V> Clone for Reordered<K, V> {
+    fn clone(&self) -> Self {
+        Reordered {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for Reordered<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Reordered")
+    }
+}
+
+/// Triggers the events queued by the [`ObservedIndexMap<K, V, S>`][ObservedIndexMap]
+/// resource, if it exists.
+///
+/// The observers run when the commands of the system are applied. The resource is not
+/// marked as changed by this system.
+///
+/// The events of the map are only triggered if this system is added to a schedule, once
+/// for each key, value and hasher types of the maps.
+pub fn trigger_index_map_events<K, V, S>(
+    map: Option<ResMut<'_, ObservedIndexMap<K, V, S>>>,
+    mut commands: Commands<'_, '_>,
+) where
+    K: Send + Sync + 'static,
+    V: Send + Sync + 'static,
+    S: Send + Sync + 'static,
+{
+    if let Some(mut map) = map {
+        map.bypass_change_detection().trigger_events(&mut commands);
+    }
+}
+
+impl<K, V, S> Default for ObservedIndexMap<K, V, S>
+where
+    S: Default,
+{
+    /// Return an empty [`ObservedIndexMap`]
+    fn default() -> Self {
+        Self::from_map(IndexMap::default())
+    }
+}
+
+impl<K, V, S> fmt::Debug for ObservedIndexMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.map, f)
+    }
+}
+
+impl<K, V, S> Deref for ObservedIndexMap<K, V, S> {
+    type Target = IndexMap<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K, V, S> ObservedIndexMap<K, V, S> {
+    /// Wraps `map`, without queuing events for its entries.
+    pub const fn from_map(map: IndexMap<K, V, S>) -> Self {
+        ObservedIndexMap {
+            map,
+            queued: Vec::new(),
+        }
+    }
+
+    /// Returns the inner [`IndexMap`], and drops the queued events.
+    pub fn into_inner(self) -> IndexMap<K, V, S> {
+        self.map
+    }
+
+    /// Returns the number of queued events.
+    pub fn queued_events(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Triggers the queued events with `commands`, in the order of the changes.
+    pub fn trigger_events(&mut self, commands: &mut Commands<'_, '_>)
+    where
+        K: Send + Sync + 'static,
+        V: Send + Sync + 'static,
+    {
+        for queued in self.queued.drain(..) {
+            match queued {
+                Queued::Inserted { key, index } => commands.trigger(EntryInserted::<K, V> {
+                    key,
+                    index,
+                    marker: PhantomData,
+                }),
+                Queued::Removed { key, index } => commands.trigger(EntryRemoved::<K, V> {
+                    key,
+                    index,
+                    marker: PhantomData,
+                }),
+                Queued::RemovedBatch { keys } => commands.trigger(EntriesRemoved::<K, V> {
+                    keys,
+                    marker: PhantomData,
+                }),
+                Queued::Moved { key, from, to } => commands.trigger(EntryMoved::<K, V> {
+                    key,
+                    from,
+                    to,
+                    marker: PhantomData,
+                }),
+                Queued::Reordered => commands.trigger(Reordered::<K, V> {
+                    marker: PhantomData,
+                }),
+            }
+        }
+    }
+
+    /// Get a key-value pair by index, for mutation, without queuing any event.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.map.get_index_mut(index)
+    }
+
+    /// Return an iterator over mutable references to the values of the map, in their
+    /// order, without queuing any event.
+    pub fn values_mut(&mut self) -> map::ValuesMut<'_, K, V> {
+        self.map.values_mut()
+    }
+
+    /// Sort the map's key-value pairs in place using the comparison function `cmp`,
+    /// and queue a [`Reordered`] event.
+    ///
+    /// See [`IndexMap::sort_by`].
+    pub fn sort_by<F>(&mut self, cmp: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.map.sort_by(cmp);
+        self.queued.push(Queued::Reordered);
+    }
+
+    /// Sort the map's key-value pairs by the default ordering of the keys, and queue a
+    /// [`Reordered`] event.
+    ///
+    /// See [`IndexMap::sort_keys`].
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.map.sort_keys();
+        self.queued.push(Queued::Reordered);
+    }
+
+    /// Reverses the order of the map's key-value pairs in place, and queue a
+    /// [`Reordered`] event.
+    ///
+    /// See [`IndexMap::reverse`].
+    pub fn reverse(&mut self) {
+        self.map.reverse();
+        self.queued.push(Queued::Reordered);
+    }
+}
+
+impl<K, V, S> ObservedIndexMap<K, V, S>
+where
+    K: Clone,
+{
+    /// Moves the position of a key-value pair from one index to another by shifting all
+    /// other pairs in-between, and queue an [`EntryMoved`] event.
+    ///
+    /// See [`IndexMap::move_index`].
+    ///
+    /// ***Panics*** if `from` or `to` are out of bounds.
+    #[track_caller]
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.map.move_index(from, to);
+        self.queue_moved(to, from);
+    }
+
+    /// Swaps the position of two key-value pairs in the map, and queue an
+    /// [`EntryMoved`] event for each of them.
+    ///
+    /// See [`IndexMap::swap_indices`].
+    ///
+    /// ***Panics*** if `a` or `b` are out of bounds.
+    #[track_caller]
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.map.swap_indices(a, b);
+        if a != b {
+            self.queue_moved(b, a);
+            self.queue_moved(a, b);
+        }
+    }
+
+    /// Remove the key-value pair by index, like [`Vec::swap_remove`], and queue an
+    /// [`EntryRemoved`] event, and an [`EntryMoved`] event for the last pair if it
+    /// takes its place.
+    ///
+    /// See [`IndexMap::swap_remove_index`].
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        let (key, value) = self.map.swap_remove_index(index)?;
+        self.queued.push(Queued::Removed {
+            key: key.clone(),
+            index,
+        });
+        self.queue_moved(index, self.map.len());
+        Some((key, value))
+    }
+
+    /// Remove the key-value pair by index, like [`Vec::remove`], and queue an
+    /// [`EntryRemoved`] event.
+    ///
+    /// See [`IndexMap::shift_remove_index`].
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<(K, V)> {
+        let (key, value) = self.map.shift_remove_index(index)?;
+        self.queued.push(Queued::Removed {
+            key: key.clone(),
+            index,
+        });
+        Some((key, value))
+    }
+
+    /// Remove the key-value pairs in the given index range, and return them in an
+    /// iterator. An [`EntriesRemoved`] event is queued for them, unless the range is
+    /// empty.
+    ///
+    /// See [`IndexMap::drain`].
+    ///
+    /// ***Panics*** if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the map.
+    #[track_caller]
+    pub fn drain<R>(&mut self, range: R) -> map::Drain<'_, K, V>
+    where
+        R: RangeBounds<usize>,
+    {
+        let range = crate::util::simplify_range(range, self.map.len());
+        // The drained entries are removed even if the iterator isn't consumed.
+        let keys: Vec<(usize, K)> = range
+            .clone()
+            .zip(self.map.as_slice()[range.clone()].keys().cloned())
+            .collect();
+        self.queue_removed(keys);
+        self.map.drain(range)
+    }
+
+    /// Remove all key-value pairs in the map, and queue an [`EntriesRemoved`] event for
+    /// them, unless the map is empty.
+    pub fn clear(&mut self) {
+        self.drain(..);
+    }
+
+    /// Retains only the elements specified by the predicate, and queue an
+    /// [`EntriesRemoved`] event for the others, unless every element is kept.
+    ///
+    /// The predicate gets a mutable reference to the values, without queuing any event.
+    ///
+    /// See [`IndexMap::retain`].
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let mut keys = Vec::new();
+        let mut index = 0;
+        self.map.retain(|key, value| {
+            let kept = keep(key, value);
+            if !kept {
+                keys.push((index, key.clone()));
+            }
+            index += 1;
+            kept
+        });
+        self.queue_removed(keys);
+    }
+
+    /// Queues an [`EntriesRemoved`] event for `keys`, if there are any.
+    fn queue_removed(&mut self, keys: Vec<(usize, K)>) {
+        if !keys.is_empty() {
+            self.queued.push(Queued::RemovedBatch { keys });
+        }
+    }
+
+    /// Queues an [`EntryMoved`] event for the entry now at index `to`, if it exists.
+    fn queue_moved(&mut self, to: usize, from: usize) {
+        if from != to {
+            if let Some((key, _)) = self.map.get_index(to) {
+                let key = key.clone();
+                self.queued.push(Queued::Moved { key, from, to });
+            }
+        }
+    }
+}
+
+impl<K, V, S> ObservedIndexMap<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    /// Insert a key-value pair in the map.
+    ///
+    /// If an equivalent key already exists in the map, its value is replaced, the old
+    /// value is returned, and no event is queued. Otherwise, the new pair is inserted
+    /// at the end of the map, and an [`EntryInserted`] event is queued.
+    ///
+    /// See [`IndexMap::insert`].
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_full(key, value).1
+    }
+
+    /// Insert a key-value pair in the map, and get its index, like
+    /// [`insert`][Self::insert].
+    ///
+    /// See [`IndexMap::insert_full`].
+    pub fn insert_full(&mut self, key: K, value: V) -> (usize, Option<V>) {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => (entry.index(), Some(entry.insert(value))),
+            Entry::Vacant(entry) => {
+                let index = entry.index();
+                entry.insert(value);
+                (index, None)
+            }
+        }
+    }
+
+    /// Get the given key's corresponding entry in the map for insertion and/or in-place
+    /// manipulation, which queues the events of the changes.
+    ///
+    /// See [`IndexMap::entry`].
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let queued = &mut self.queued;
+        match self.map.entry(key) {
+            map::Entry::Occupied(entry) => Entry::Occupied(OccupiedEntry { entry, queued }),
+            map::Entry::Vacant(entry) => Entry::Vacant(VacantEntry { entry, queued }),
+        }
+    }
+
+    /// Return a mutable reference to the value stored for `key`, if it is present,
+    /// without queuing any event.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.get_mut(key)
+    }
+
+    /// Remove the key-value pair equivalent to `key` and return its value, like
+    /// [`Vec::swap_remove`], and queue the events of
+    /// [`swap_remove_index`][Self::swap_remove_index].
+    ///
+    /// See [`IndexMap::swap_remove`].
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of(key)?;
+        self.swap_remove_index(index).map(|(_, value)| value)
+    }
+
+    /// Remove the key-value pair equivalent to `key` and return its value, like
+    /// [`Vec::remove`], and queue an [`EntryRemoved`] event.
+    ///
+    /// See [`IndexMap::shift_remove`].
+    pub fn shift_remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = self.map.get_index_of(key)?;
+        self.shift_remove_index(index).map(|(_, value)| value)
+    }
+}
+
+/// Entry for an existing key-value pair in an [`ObservedIndexMap`]
+/// or a vacant location to insert one.
+///
+/// This `enum` is created by the [`ObservedIndexMap::entry`] method.
+pub enum Entry<'a, K, V> {
+    /// Existing slot with equivalent key.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// Vacant slot (no equivalent key in the map).
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: Clone, V> Entry<'a, K, V> {
+    /// Return the index where the key-value pair exists or will be inserted.
+    pub fn index(&self) -> usize {
+        match self {
+            Entry::Occupied(entry) => entry.index(),
+            Entry::Vacant(entry) => entry.index(),
+        }
+    }
+
+    /// Gets a reference to the entry's key, either within the map if occupied,
+    /// or else the new key that was used to find the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts the given default value in the entry if it is vacant and returns a mutable
+    /// reference to it. Otherwise a mutable reference to an already existent value is returned.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Inserts the result of the `call` function in the entry if it is vacant and returns a mutable
+    /// reference to it. Otherwise a mutable reference to an already existent value is returned.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn or_insert_with<F>(self, call: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(call()),
+        }
+    }
+
+    /// Inserts a default-constructed value in the entry if it is vacant and returns a mutable
+    /// reference to it. Otherwise a mutable reference to an already existent value is returned.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Modifies the entry if it is occupied, without queuing any event.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Entry<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("Entry");
+        match self {
+            Entry::Vacant(v) => tuple.field(v),
+            Entry::Occupied(o) => tuple.field(o),
+        };
+        tuple.finish()
+    }
+}
+
+/// A view into an occupied entry in an [`ObservedIndexMap`].
+/// It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, K, V> {
+    entry: map::OccupiedEntry<'a, K, V>,
+    queued: &'a mut Vec<Queued<K>>,
+}
+
+impl<'a, K: Clone, V> OccupiedEntry<'a, K, V> {
+    /// Return the index of the key-value pair
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.entry.index()
+    }
+
+    /// Gets a reference to the entry's key in the map.
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Gets a reference to the entry's value in the map.
+    pub fn get(&self) -> &V {
+        self.entry.get()
+    }
+
+    /// Gets a mutable reference to the entry's value in the map, without queuing any event.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.entry.get_mut()
+    }
+
+    /// Converts into a mutable reference to the entry's value in the map,
+    /// with a lifetime bound to the map itself, without queuing any event.
+    pub fn into_mut(self) -> &'a mut V {
+        self.entry.into_mut()
+    }
+
+    /// Sets the value of the entry to `value`, and returns the entry's old value,
+    /// without queuing any event.
+    pub fn insert(&mut self, value: V) -> V {
+        self.entry.insert(value)
+    }
+
+    /// Remove the key, value pair stored in the map for this entry, and return the value,
+    /// like [`ObservedIndexMap::swap_remove_index`].
+    pub fn swap_remove(self) -> V {
+        self.swap_remove_entry().1
+    }
+
+    /// Remove the key, value pair stored in the map for this entry, and return the value,
+    /// like [`ObservedIndexMap::shift_remove_index`].
+    pub fn shift_remove(self) -> V {
+        self.shift_remove_entry().1
+    }
+
+    /// Remove and return the key, value pair stored in the map for this entry,
+    /// like [`ObservedIndexMap::swap_remove_index`].
+    pub fn swap_remove_entry(self) -> (K, V) {
+        let index = self.entry.index();
+        let last = self.entry.map_len() - 1;
+        let moved = if index != last {
+            self.entry.key_at(last).cloned()
+        } else {
+            None
+        };
+        let (key, value) = self.entry.swap_remove_entry();
+        self.queued.push(Queued::Removed {
+            key: key.clone(),
+            index,
+        });
+        if let Some(moved) = moved {
+            self.queued.push(Queued::Moved {
+                key: moved,
+                from: last,
+                to: index,
+            });
+        }
+        (key, value)
+    }
+
+    /// Remove and return the key, value pair stored in the map for this entry,
+    /// like [`ObservedIndexMap::shift_remove_index`].
+    pub fn shift_remove_entry(self) -> (K, V) {
+        let index = self.entry.index();
+        let (key, value) = self.entry.shift_remove_entry();
+        self.queued.push(Queued::Removed {
+            key: key.clone(),
+            index,
+        });
+        (key, value)
+    }
+
+    /// Moves the position of the entry to a new index, like
+    /// [`ObservedIndexMap::move_index`].
+    ///
+    /// ***Panics*** if `to` is out of bounds.
+    #[track_caller]
+    pub fn move_index(self, to: usize) {
+        let from = self.entry.index();
+        let key = self.entry.key().clone();
+        self.entry.move_index(to);
+        if from != to {
+            self.queued.push(Queued::Moved { key, from, to });
+        }
+    }
+
+    /// Swaps the position of the entry with another, like
+    /// [`ObservedIndexMap::swap_indices`].
+    ///
+    /// ***Panics*** if the `other` index is out of bounds.
+    #[track_caller]
+    pub fn swap_indices(self, other: usize) {
+        let index = self.entry.index();
+        let keys = if index != other {
+            Some((self.entry.key().clone(), self.entry.key_at(other).cloned()))
+        } else {
+            None
+        };
+        self.entry.swap_indices(other);
+        if let Some((key, Some(other_key))) = keys {
+            self.queued.push(Queued::Moved {
+                key,
+                from: index,
+                to: other,
+            });
+            self.queued.push(Queued::Moved {
+                key: other_key,
+                from: other,
+                to: index,
+            });
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OccupiedEntry<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.entry.key())
+            .field("value", self.entry.get())
+            .finish()
+    }
+}
+
+/// A view into a vacant entry in an [`ObservedIndexMap`].
+/// It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, K, V> {
+    entry: map::VacantEntry<'a, K, V>,
+    queued: &'a mut Vec<Queued<K>>,
+}
+
+impl<'a, K: Clone, V> VacantEntry<'a, K, V> {
+    /// Return the index where a key-value pair may be inserted.
+    pub fn index(&self) -> usize {
+        self.entry.index()
+    }
+
+    /// Gets a reference to the key that was used to find the entry.
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Takes ownership of the key, leaving the entry vacant.
+    pub fn into_key(self) -> K {
+        self.entry.into_key()
+    }
+
+    /// Inserts the entry's key and the given value into the map, queues an
+    /// [`EntryInserted`] event, and returns a mutable reference to the value.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.queued.push(Queued::Inserted {
+            key: self.entry.key().clone(),
+            index: self.entry.index(),
+        });
+        self.entry.insert(value)
+    }
+
+    /// Inserts the entry's key and the given value into the map at the given index,
+    /// shifting others to the right, queues an [`EntryInserted`] event, and returns a
+    /// mutable reference to the value.
+    ///
+    /// See [`map::VacantEntry::shift_insert`].
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    #[track_caller]
+    pub fn shift_insert(self, index: usize, value: V) -> &'a mut V {
+        let key = self.entry.key().clone();
+        let value = self.entry.shift_insert(index, value);
+        self.queued.push(Queued::Inserted { key, index });
+        value
+    }
+}
+
+impl<K: fmt::Debug, V> fmt::Debug for VacantEntry<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry")
+            .field(self.entry.key())
+            .finish()
+    }
+}
@@ -0,0 +1,462 @@
+//! [`OrderedRegistry`] maps keys to entities in order, and forgets despawned entities.
+
+use crate::map::{self, IndexMap};
+use crate::Equivalent;
+
+use super::set::{self, EntityIndexSet};
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+
+use bevy_ecs::component::{Component, ComponentHook, HookContext, Immutable, StorageType};
+use bevy_ecs::entity::Entity;
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::reflect::ReflectResource;
+use bevy_ecs::resource::Resource;
+use bevy_ecs::system::{Commands, ResMut, SystemParam};
+use bevy_ecs::world::{DeferredWorld, World};
+
+/// A resource that maps keys to entities, in order, with lookups both ways.
+///
+/// Each key is mapped to one entity and each entity to one key, and both lookups take
+/// **O(1)** time. The entries keep their order until they are explicitly reordered, and
+/// removing an entry shifts the following ones, like [`IndexMap::shift_remove`].
+///
+/// Entries are added and removed with the [`OrderedRegistryMut`] system parameter,
+/// which also inserts the [`Registered`] component on the registered entities: when an
+/// entity is despawned, or loses that component, its entry is removed by the component's
+/// `on_remove` hook.
+///
+/// With the `bevy_reflect` feature, the registry implements `Reflect` and registers
+/// `ReflectResource`, so that it shows up in inspectors once its type is registered,
+/// for example with `App::register_type::<OrderedRegistry<K>>()`.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use bevy_ecs::system::RunSystemOnce;
+/// use indexmap::ecs::registry::{OrderedRegistry, OrderedRegistryMut};
+///
+/// let mut world = World::new();
+/// world.init_resource::<OrderedRegistry<u32>>();
+/// let left = world.spawn_empty().id();
+/// let right = world.spawn_empty().id();
+///
+/// world
+///     .run_system_once(move |mut monitors: OrderedRegistryMut<u32>| {
+///         monitors.register(7, left);
+///         monitors.register(3, right);
+///     })
+///     .unwrap();
+///
+/// let monitors = world.resource::<OrderedRegistry<u32>>();
+/// assert_eq!(monitors.get(&3), Some(right));
+/// assert_eq!(monitors.get_key(left), Some(&7));
+/// assert!(monitors.keys().eq(&[7, 3]));
+///
+/// world.despawn(left);
+/// let monitors = world.resource::<OrderedRegistry<u32>>();
+/// assert!(monitors.entities().eq(&[right]));
+/// assert_eq!(monitors.get_index_of_entity(right), Some(0));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Resource)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct OrderedRegistry<K, S = RandomState> {
+    by_key: IndexMap<K, Entity, S>,
+    entities: EntityIndexSet,
+}
+#[cfg(not(feature = "std"))]
+#[derive(Resource)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource)
+)]
+pub struct OrderedRegistry<K, S> {
+    by_key: IndexMap<K, Entity, S>,
+    entities: EntityIndexSet,
+}
+
+impl<K, S> OrderedRegistry<K, S> {
+    /// Create an empty registry, using `hasher` for the keys.
+    pub const fn with_hasher(hasher: S) -> Self {
+        OrderedRegistry {
+            by_key: IndexMap::with_hasher(hasher),
+            entities: EntityIndexSet::new(),
+        }
+    }
+
+    /// Return the number of registered entities.
+    pub fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
+    /// Returns true if no entity is registered.
+    pub fn is_empty(&self) -> bool {
+        self.by_key.is_empty()
+    }
+
+    /// Return an iterator over the keys and their entities, in order.
+    pub fn iter(&self) -> map::Iter<'_, K, Entity> {
+        self.by_key.iter()
+    }
+
+    /// Return an iterator over the keys, in order.
+    pub fn keys(&self) -> map::Keys<'_, K, Entity> {
+        self.by_key.keys()
+    }
+
+    /// Return an iterator over the registered entities, in order.
+    ///
+    /// The iterator is an `EntitySetIterator`, so it can be passed directly to APIs
+    /// like `Query::iter_many_unique_mut`.
+    pub fn entities(&self) -> set::Iter<'_> {
+        self.entities.iter()
+    }
+
+    /// Return the key of `entity`, if it is registered.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_key(&self, entity: Entity) -> Option<&K> {
+        let index = self.entities.get_index_of(&entity)?;
+        Some(self.by_key.get_index(index)?.0)
+    }
+
+    /// Return the index of `entity`, if it is registered.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of_entity(&self, entity: Entity) -> Option<usize> {
+        self.entities.get_index_of(&entity)
+    }
+
+    /// Returns true if `entity` is registered.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains_entity(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    /// Get a key and its entity by index.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<(&K, Entity)> {
+        let (key, &entity) = self.by_key.get_index(index)?;
+        Some((key, entity))
+    }
+
+    /// Moves the position of an entry from one index to another by shifting all other
+    /// entries in-between.
+    ///
+    /// See [`IndexMap::move_index`].
+    ///
+    /// ***Panics*** if `from` or `to` are out of bounds.
+    #[track_caller]
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.by_key.move_index(from, to);
+        self.entities.move_index(from, to);
+    }
+
+    /// Swaps the position of two entries.
+    ///
+    /// ***Panics*** if `a` or `b` are out of bounds.
+    #[track_caller]
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.by_key.swap_indices(a, b);
+        self.entities.swap_indices(a, b);
+    }
+
+    /// Sort the entries by the default ordering of the keys.
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.sort_by(|k1, _, k2, _| Ord::cmp(k1, k2));
+    }
+
+    /// Sort the entries in place using the comparison function `cmp`, which gets the
+    /// keys and their entities.
+    ///
+    /// The sort is stable.
+    pub fn sort_by<F>(&mut self, mut cmp: F)
+    where
+        F: FnMut(&K, Entity, &K, Entity) -> Ordering,
+    {
+        self.by_key.sort_by(|k1, e1, k2, e2| cmp(k1, *e1, k2, *e2));
+        self.entities.clear();
+        self.entities.extend(self.by_key.values().copied());
+    }
+
+    /// Remove the entry of `entity` and return its key, shifting the following entries.
+    fn remove_entity(&mut self, entity: Entity) -> Option<K> {
+        let index = self.entities.shift_remove_full(&entity)?.0;
+        Some(self.by_key.shift_remove_index(index)?.0)
+    }
+}
+
+impl<K, S> OrderedRegistry<K, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Return the entity registered with `key`, if any.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get<Q>(&self, key: &Q) -> Option<Entity>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.by_key.get(key).copied()
+    }
+
+    /// Return the index of `key`, if it is registered.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.by_key.get_index_of(key)
+    }
+
+    /// Returns true if an entity is registered with `key`.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.by_key.contains_key(key)
+    }
+
+    /// Register `entity` with `key`, and return the entity that was registered with
+    /// `key` before, if any.
+    ///
+    /// A new key is added at the end, and an existing key keeps its index. If `entity`
+    /// was registered with another key, that entry is removed first.
+    fn insert(&mut self, key: K, entity: Entity) -> Option<Entity> {
+        if self.get(&key) == Some(entity) {
+            return Some(entity);
+        }
+        self.remove_entity(entity);
+        match self.by_key.entry(key) {
+            map::Entry::Occupied(mut entry) => {
+                let index = entry.index();
+                let old = entry.insert(entity);
+                // The new entity takes the place of the old one.
+                self.entities.insert(entity);
+                self.entities.swap_remove_index(index);
+                Some(old)
+            }
+            map::Entry::Vacant(entry) => {
+                entry.insert(entity);
+                self.entities.insert(entity);
+                None
+            }
+        }
+    }
+
+    /// Remove the entry of `key` and return its entity, shifting the following entries.
+    fn remove<Q>(&mut self, key: &Q) -> Option<Entity>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let (index, _, entity) = self.by_key.shift_remove_full(key)?;
+        self.entities.shift_remove_index(index);
+        Some(entity)
+    }
+}
+
+impl<K, S> Default for OrderedRegistry<K, S>
+where
+    S: Default,
+{
+    /// Return an empty [`OrderedRegistry`]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, S> fmt::Debug for OrderedRegistry<K, S>
+where
+    K: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, S> IntoIterator for &'a OrderedRegistry<K, S> {
+    type Item = (&'a K, &'a Entity);
+    type IntoIter = map::Iter<'a, K, Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The component that ties an entity to its entry in an [`OrderedRegistry<K, S>`].
+///
+/// It is inserted by [`OrderedRegistryMut::register`], and its `on_remove` hook removes
+/// the entity from the registry, when the entity is despawned or the component is
+/// removed.
+#[cfg(feature = "std")]
+pub struct Registered<K, S = RandomState>(PhantomData<fn() -> (K, S)>);
+#[cfg(not(feature = "std"))]
+pub struct Registered<K, S>(PhantomData<fn() -> (K, S)>);
+
+impl<K, S> Registered<K, S> {
+    const fn new() -> Self {
+        Registered(PhantomData)
+    }
+}
+
+impl<K, S> fmt::Debug for Registered<K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Registered")
+    }
+}
+
+impl<K, S> Component for Registered<K, S>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    const STORAGE_TYPE: StorageType = StorageType::SparseSet;
+    type Mutability = Immutable;
+
+    fn on_remove() -> Option<ComponentHook> {
+        Some(unregister_removed::<K, S>)
+    }
+}
+
+fn unregister_removed<K, S>(mut world: DeferredWorld<'_>, context: HookContext)
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    if let Some(mut registry) = world.get_resource_mut::<OrderedRegistry<K, S>>() {
+        registry.remove_entity(context.entity);
+    }
+}
+
+/// A [`SystemParam`] to register entities in the [`OrderedRegistry<K, S>`] resource.
+///
+/// Changes to the registry take effect immediately, while the [`Registered`] components
+/// are inserted and removed when the commands are applied. An entity that was despawned
+/// by then is removed from the registry again.
+///
+/// The registry can be read and reordered through [`Deref`] and [`DerefMut`].
+#[cfg(feature = "std")]
+#[derive(SystemParam)]
+pub struct OrderedRegistryMut<'w, 's, K, S = RandomState>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    registry: ResMut<'w, OrderedRegistry<K, S>>,
+    commands: Commands<'w, 's>,
+}
+#[cfg(not(feature = "std"))]
+#[derive(SystemParam)]
+pub struct OrderedRegistryMut<'w, 's, K, S>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    registry: ResMut<'w, OrderedRegistry<K, S>>,
+    commands: Commands<'w, 's>,
+}
+
+impl<K, S> OrderedRegistryMut<'_, '_, K, S>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    /// Register `entity` with `key`, and return the entity that was registered with
+    /// `key` before, if any.
+    ///
+    /// A new key is added at the end, and an existing key keeps its index. If `entity`
+    /// was registered with another key, that entry is removed first.
+    pub fn register(&mut self, key: K, entity: Entity) -> Option<Entity> {
+        let old = self.registry.insert(key, entity);
+        self.commands.queue(move |world: &mut World| {
+            if let Ok(mut entity) = world.get_entity_mut(entity) {
+                entity.insert(Registered::<K, S>::new());
+            } else if let Some(mut registry) = world.get_resource_mut::<OrderedRegistry<K, S>>() {
+                registry.remove_entity(entity);
+            }
+        });
+        if let Some(old) = old {
+            self.queue_unregistered(old);
+        }
+        old
+    }
+
+    /// Remove the entry of `key` and return its entity, shifting the following entries.
+    pub fn unregister<Q>(&mut self, key: &Q) -> Option<Entity>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let entity = self.registry.remove(key)?;
+        self.queue_unregistered(entity);
+        Some(entity)
+    }
+
+    /// Remove the entry of `entity` and return its key, shifting the following entries.
+    pub fn unregister_entity(&mut self, entity: Entity) -> Option<K> {
+        let key = self.registry.remove_entity(entity)?;
+        self.queue_unregistered(entity);
+        Some(key)
+    }
+
+    /// Queues the removal of the [`Registered`] component of `entity`, unless it was
+    /// registered again by then.
+    fn queue_unregistered(&mut self, entity: Entity) {
+        self.commands.queue(move |world: &mut World| {
+            let registered = world
+                .get_resource::<OrderedRegistry<K, S>>()
+                .map_or(false, |registry| registry.contains_entity(entity));
+            if !registered {
+                if let Ok(mut entity) = world.get_entity_mut(entity) {
+                    entity.remove::<Registered<K, S>>();
+                }
+            }
+        });
+    }
+}
+
+impl<K, S> Deref for OrderedRegistryMut<'_, '_, K, S>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    type Target = OrderedRegistry<K, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.registry
+    }
+}
+
+impl<K, S> DerefMut for OrderedRegistryMut<'_, '_, K, S>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.registry
+    }
+}
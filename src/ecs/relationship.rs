@@ -0,0 +1,293 @@
+use crate::IndexSet;
+
+use alloc::boxed::Box;
+use core::cmp::Ordering;
+
+use bevy_ecs::component::Component;
+use bevy_ecs::entity::hash_map::EntityHashMap;
+use bevy_ecs::entity::{Entity, EntityHash};
+use bevy_ecs::relationship::{Relationship, RelationshipTarget};
+use bevy_ecs::system::EntityCommands;
+use bevy_ecs::world::{EntityWorldMut, World};
+
+/// Methods to reorder the entities related to an entity, when the relationship target
+/// stores them in an [`IndexSet<Entity, EntityHash>`][IndexSet].
+///
+/// These complement the related methods of [`EntityWorldMut`] and [`EntityCommands`],
+/// like `add_related` and `insert_related`. Entities only change position in the
+/// relationship target, except for [`insert_related_before`][Self::insert_related_before]
+/// and [`insert_related_after`][Self::insert_related_after], which also relate the given
+/// entities first if needed, through the relationship component. The relationship
+/// invariants are kept in either case.
+///
+/// Indices out of bounds are clamped to the last entity when they are destinations,
+/// and make the operation do nothing when they are sources.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use bevy_ecs::entity::EntityHash;
+/// use indexmap::ecs::OrderedRelated;
+/// use indexmap::IndexSet;
+///
+/// #[derive(Component)]
+/// #[relationship(relationship_target = Panes)]
+/// struct PaneOf(Entity);
+///
+/// #[derive(Component)]
+/// #[relationship_target(relationship = PaneOf)]
+/// struct Panes(IndexSet<Entity, EntityHash>);
+///
+/// let mut world = World::new();
+/// let window = world.spawn_empty().id();
+/// let a = world.spawn(PaneOf(window)).id();
+/// let b = world.spawn(PaneOf(window)).id();
+/// let c = world.spawn_empty().id();
+///
+/// let mut window = world.entity_mut(window);
+/// window.insert_related_before::<PaneOf>(a, &[c]);
+/// window.move_related_index::<PaneOf>(2, 0);
+///
+/// assert!(window.get::<Panes>().unwrap().0.iter().eq(&[b, c, a]));
+/// ```
+pub trait OrderedRelated {
+    /// Relates `related` to this entity and puts them right before `sibling`, in order.
+    ///
+    /// If `sibling` is not related to this entity, the entities that were not related yet
+    /// are added at the end, and the others keep their position.
+    fn insert_related_before<R>(&mut self, sibling: Entity, related: &[Entity]) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>;
+
+    /// Relates `related` to this entity and puts them right after `sibling`, in order.
+    ///
+    /// If `sibling` is not related to this entity, the entities that were not related yet
+    /// are added at the end, and the others keep their position.
+    fn insert_related_after<R>(&mut self, sibling: Entity, related: &[Entity]) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>;
+
+    /// Moves the related entity at index `from` to index `to`, shifting the entities
+    /// in between.
+    ///
+    /// See [`IndexSet::move_index`].
+    fn move_related_index<R>(&mut self, from: usize, to: usize) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>;
+
+    /// Swaps the positions of the related entities at indices `a` and `b`.
+    ///
+    /// See [`IndexSet::swap_indices`].
+    fn swap_related_indices<R>(&mut self, a: usize, b: usize) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>;
+
+    /// Sorts the related entities by a key extracted from their component `C`.
+    ///
+    /// The sort is stable, and entities without the component are put at the end.
+    ///
+    /// See [`IndexSet::sort_by`].
+    fn sort_related_by_key<R, C, K, F>(&mut self, key: F) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+        C: Component,
+        K: Ord,
+        F: FnMut(&C) -> K + Send + 'static;
+}
+
+impl OrderedRelated for EntityWorldMut<'_> {
+    fn insert_related_before<R>(&mut self, sibling: Entity, related: &[Entity]) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+    {
+        insert_related_next_to::<R>(self, sibling, related, false);
+        self
+    }
+
+    fn insert_related_after<R>(&mut self, sibling: Entity, related: &[Entity]) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+    {
+        insert_related_next_to::<R>(self, sibling, related, true);
+        self
+    }
+
+    fn move_related_index<R>(&mut self, from: usize, to: usize) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+    {
+        if let Some(mut target) = self.get_mut::<R::RelationshipTarget>() {
+            let collection = target.collection_mut_risky();
+            if from < collection.len() {
+                let to = to.min(collection.len() - 1);
+                collection.move_index(from, to);
+            }
+        }
+        self
+    }
+
+    fn swap_related_indices<R>(&mut self, a: usize, b: usize) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+    {
+        if let Some(mut target) = self.get_mut::<R::RelationshipTarget>() {
+            let collection = target.collection_mut_risky();
+            if a < collection.len() && b < collection.len() {
+                collection.swap_indices(a, b);
+            }
+        }
+        self
+    }
+
+    fn sort_related_by_key<R, C, K, F>(&mut self, mut key: F) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+        C: Component,
+        K: Ord,
+        F: FnMut(&C) -> K + Send + 'static,
+    {
+        let id = self.id();
+        self.world_scope(|world| {
+            // The keys are read before the relationship target is borrowed mutably.
+            let keys: EntityHashMap<Option<K>> = match world.get::<R::RelationshipTarget>(id) {
+                Some(target) => target
+                    .collection()
+                    .iter()
+                    .map(|&entity| (entity, world.get::<C>(entity).map(&mut key)))
+                    .collect(),
+                None => return,
+            };
+
+            let mut target = world
+                .get_mut::<R::RelationshipTarget>(id)
+                .expect("the relationship target was just read");
+            target
+                .collection_mut_risky()
+                .sort_by(|a, b| match (&keys[a], &keys[b]) {
+                    (Some(a), Some(b)) => a.cmp(b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                });
+        });
+        self
+    }
+}
+
+impl OrderedRelated for EntityCommands<'_> {
+    fn insert_related_before<R>(&mut self, sibling: Entity, related: &[Entity]) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+    {
+        let related: Box<[Entity]> = related.into();
+        self.queue(move |mut entity: EntityWorldMut<'_>| {
+            entity.insert_related_before::<R>(sibling, &related);
+        })
+    }
+
+    fn insert_related_after<R>(&mut self, sibling: Entity, related: &[Entity]) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+    {
+        let related: Box<[Entity]> = related.into();
+        self.queue(move |mut entity: EntityWorldMut<'_>| {
+            entity.insert_related_after::<R>(sibling, &related);
+        })
+    }
+
+    fn move_related_index<R>(&mut self, from: usize, to: usize) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+    {
+        self.queue(move |mut entity: EntityWorldMut<'_>| {
+            entity.move_related_index::<R>(from, to);
+        })
+    }
+
+    fn swap_related_indices<R>(&mut self, a: usize, b: usize) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+    {
+        self.queue(move |mut entity: EntityWorldMut<'_>| {
+            entity.swap_related_indices::<R>(a, b);
+        })
+    }
+
+    fn sort_related_by_key<R, C, K, F>(&mut self, key: F) -> &mut Self
+    where
+        R: Relationship,
+        R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+        C: Component,
+        K: Ord,
+        F: FnMut(&C) -> K + Send + 'static,
+    {
+        self.queue(move |mut entity: EntityWorldMut<'_>| {
+            entity.sort_related_by_key::<R, C, K, F>(key);
+        })
+    }
+}
+
+/// Relates each entity of `related` to `entity`, and moves it next to the previous one,
+/// starting from `sibling`.
+fn insert_related_next_to<R>(
+    entity: &mut EntityWorldMut<'_>,
+    sibling: Entity,
+    related: &[Entity],
+    after: bool,
+) where
+    R: Relationship,
+    R::RelationshipTarget: RelationshipTarget<Collection = IndexSet<Entity, EntityHash>>,
+{
+    let id = entity.id();
+    entity.world_scope(|world| {
+        let mut anchor = sibling;
+        for &related in related {
+            if related == id || related == anchor {
+                continue;
+            }
+            if !is_related::<R>(world, related, id) {
+                world.entity_mut(related).insert(R::from(id));
+            }
+
+            let mut target = world
+                .get_mut::<R::RelationshipTarget>(id)
+                .expect("hooks should have added relationship target");
+            let collection = target.collection_mut_risky();
+            if let (Some(from), Some(to)) = (
+                collection.get_index_of(&related),
+                collection.get_index_of(&anchor),
+            ) {
+                let to = if after { to + 1 } else { to };
+                // Moving an entity from before the anchor shifts the anchor back.
+                let to = if from < to { to - 1 } else { to };
+                collection.move_index(from, to);
+            }
+
+            if after {
+                anchor = related;
+            }
+        }
+    });
+}
+
+fn is_related<R: Relationship>(world: &World, source: Entity, target: Entity) -> bool {
+    match world.get::<R>(source) {
+        Some(relationship) => relationship.get() == target,
+        None => false,
+    }
+}
@@ -0,0 +1,606 @@
+//! [`EntityIndexSet`] is an [`IndexSet`] of entities that `bevy_ecs` knows to be unique.
+
+use super::UniqueEntityIndexSlice;
+use crate::set::{self, IndexSet};
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::ops::{Deref, RangeBounds};
+
+use bevy_ecs::entity::{
+    Entity, EntityHash, EntityMapper, EntitySet, EntitySetIterator, FromEntitySetIterator,
+    MapEntities,
+};
+
+/// An [`IndexSet`] pre-configured to use [`EntityHash`] hashing.
+///
+/// This is the ordered counterpart of `bevy_ecs`'s `EntityHashSet`: its iterators and
+/// slices are known to yield unique entities, so they can be passed directly to APIs
+/// like `Query::iter_many_unique_mut`. Read-only operations are available through
+/// [`Deref`] to the inner [`IndexSet`], and the mutating ones are forwarded by methods
+/// of the same names. There is no `DerefMut`, since the inner set could then be used
+/// with [`MutableValues`][crate::set::MutableValues], which can make two entities equal.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_ecs::entity::{Entity, EntitySetIterator};
+/// use indexmap::ecs::EntityIndexSet;
+///
+/// let a = Entity::from_raw(1);
+/// let b = Entity::from_raw(2);
+/// let set: EntityIndexSet = [b, a, b].into();
+/// assert_eq!(set.len(), 2);
+///
+/// // The uniqueness is known to `bevy_ecs`.
+/// let copy: EntityIndexSet = set.iter().copied().collect_set();
+/// assert!(copy.iter().eq(&[b, a]));
+/// ```
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntityIndexSet(pub(crate) IndexSet<Entity, EntityHash>);
+
+impl EntityIndexSet {
+    /// Creates an empty `EntityIndexSet`.
+    ///
+    /// Equivalent to [`IndexSet::with_hasher(EntityHash)`][IndexSet::with_hasher].
+    pub const fn new() -> Self {
+        Self(IndexSet::with_hasher(EntityHash))
+    }
+
+    /// Creates an empty `EntityIndexSet` with the specified capacity.
+    ///
+    /// Equivalent to
+    /// [`IndexSet::with_capacity_and_hasher(n, EntityHash)`][IndexSet::with_capacity_and_hasher].
+    pub fn with_capacity(n: usize) -> Self {
+        Self(IndexSet::with_capacity_and_hasher(n, EntityHash))
+    }
+
+    /// Returns the inner [`IndexSet`].
+    pub fn into_inner(self) -> IndexSet<Entity, EntityHash> {
+        self.0
+    }
+
+    /// Return an iterator over the entities of the set, in their order.
+    ///
+    /// Equivalent to [`IndexSet::iter`].
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.0.iter(), PhantomData)
+    }
+
+    /// Remove the entities in the given index range from the set, and return them
+    /// in an iterator.
+    ///
+    /// Equivalent to [`IndexSet::drain`].
+    ///
+    /// ***Panics*** if the starting point is greater than the end point or if
+    /// the end point is greater than the length of the set.
+    #[track_caller]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        Drain(self.0.drain(range), PhantomData)
+    }
+
+    /// Returns a slice of all the entities in the set.
+    ///
+    /// Equivalent to [`IndexSet::as_slice`].
+    pub fn as_slice(&self) -> &Slice {
+        #[allow(unsafe_code)]
+        // SAFETY: `EntityIndexSet` never gives mutable access to its inner set, so its
+        // entities can't have been made equal through `MutableValues`.
+        unsafe {
+            self.0.as_unique_entity_slice()
+        }
+    }
+
+    /// Returns a slice of entities in the given range of indices.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    ///
+    /// Equivalent to [`IndexSet::get_range`].
+    pub fn get_range<R: RangeBounds<usize>>(&self, range: R) -> Option<&Slice> {
+        self.as_slice().get_range(range)
+    }
+
+    /// Insert the entity into the set, and return `true` if it wasn't already present.
+    ///
+    /// Equivalent to [`IndexSet::insert`].
+    pub fn insert(&mut self, entity: Entity) -> bool {
+        self.0.insert(entity)
+    }
+
+    /// Insert the entity into the set, and return its index and `true` if it wasn't
+    /// already present.
+    ///
+    /// Equivalent to [`IndexSet::insert_full`].
+    pub fn insert_full(&mut self, entity: Entity) -> (usize, bool) {
+        self.0.insert_full(entity)
+    }
+
+    /// Insert the entity into the set at its ordered position among sorted entities.
+    ///
+    /// Equivalent to [`IndexSet::insert_sorted`].
+    pub fn insert_sorted(&mut self, entity: Entity) -> (usize, bool) {
+        self.0.insert_sorted(entity)
+    }
+
+    /// Insert the entity into the set before the entity at the given index, or at the end.
+    ///
+    /// Equivalent to [`IndexSet::insert_before`].
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    #[track_caller]
+    pub fn insert_before(&mut self, index: usize, entity: Entity) -> (usize, bool) {
+        self.0.insert_before(index, entity)
+    }
+
+    /// Insert the entity into the set at the given index.
+    ///
+    /// Equivalent to [`IndexSet::shift_insert`].
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    #[track_caller]
+    pub fn shift_insert(&mut self, index: usize, entity: Entity) -> bool {
+        self.0.shift_insert(index, entity)
+    }
+
+    /// Get the given entity's corresponding entry in the set for insertion and/or
+    /// in-place manipulation.
+    ///
+    /// Equivalent to [`IndexSet::entry`].
+    pub fn entry(&mut self, entity: Entity) -> set::Entry<'_, Entity> {
+        self.0.entry(entity)
+    }
+
+    /// Remove the entity from the set by swapping it with the last entity, and return
+    /// `true` if it was present.
+    ///
+    /// Equivalent to [`IndexSet::swap_remove`].
+    pub fn swap_remove(&mut self, entity: &Entity) -> bool {
+        self.0.swap_remove(entity)
+    }
+
+    /// Remove the entity from the set by shifting all of the entities that follow it,
+    /// and return `true` if it was present.
+    ///
+    /// Equivalent to [`IndexSet::shift_remove`].
+    pub fn shift_remove(&mut self, entity: &Entity) -> bool {
+        self.0.shift_remove(entity)
+    }
+
+    /// Remove the entity from the set by swapping it with the last entity, and return
+    /// its index and the entity if it was present.
+    ///
+    /// Equivalent to [`IndexSet::swap_remove_full`].
+    pub fn swap_remove_full(&mut self, entity: &Entity) -> Option<(usize, Entity)> {
+        self.0.swap_remove_full(entity)
+    }
+
+    /// Remove the entity from the set by shifting all of the entities that follow it,
+    /// and return its index and the entity if it was present.
+    ///
+    /// Equivalent to [`IndexSet::shift_remove_full`].
+    pub fn shift_remove_full(&mut self, entity: &Entity) -> Option<(usize, Entity)> {
+        self.0.shift_remove_full(entity)
+    }
+
+    /// Remove the entity at the given index by swapping it with the last entity.
+    ///
+    /// Equivalent to [`IndexSet::swap_remove_index`].
+    pub fn swap_remove_index(&mut self, index: usize) -> Option<Entity> {
+        self.0.swap_remove_index(index)
+    }
+
+    /// Remove the entity at the given index by shifting all of the entities that
+    /// follow it.
+    ///
+    /// Equivalent to [`IndexSet::shift_remove_index`].
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<Entity> {
+        self.0.shift_remove_index(index)
+    }
+
+    /// Remove the last entity.
+    ///
+    /// Equivalent to [`IndexSet::pop`].
+    pub fn pop(&mut self) -> Option<Entity> {
+        self.0.pop()
+    }
+
+    /// Keep only the entities for which `keep` returns `true`, in their order.
+    ///
+    /// Equivalent to [`IndexSet::retain`].
+    pub fn retain<F>(&mut self, keep: F)
+    where
+        F: FnMut(&Entity) -> bool,
+    {
+        self.0.retain(keep);
+    }
+
+    /// Remove all entities in the set, while preserving its capacity.
+    ///
+    /// Equivalent to [`IndexSet::clear`].
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Shortens the set, keeping the first `len` entities and dropping the rest.
+    ///
+    /// Equivalent to [`IndexSet::truncate`].
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    /// Splits the set into two at the given index, and returns the entities from
+    /// `at` on.
+    ///
+    /// Equivalent to [`IndexSet::split_off`].
+    ///
+    /// ***Panics*** if `at > len`.
+    #[track_caller]
+    pub fn split_off(&mut self, at: usize) -> Self {
+        Self(self.0.split_off(at))
+    }
+
+    /// Moves all entities from `other` into `self`, leaving `other` empty.
+    ///
+    /// Equivalent to [`IndexSet::append`].
+    pub fn append(&mut self, other: &mut Self) {
+        self.0.append(&mut other.0);
+    }
+
+    /// Reserve capacity for `additional` more entities.
+    ///
+    /// Equivalent to [`IndexSet::reserve`].
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    /// Shrink the capacity of the set as much as possible.
+    ///
+    /// Equivalent to [`IndexSet::shrink_to_fit`].
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+    }
+
+    /// Sort the entities of the set in place.
+    ///
+    /// Equivalent to [`IndexSet::sort`].
+    pub fn sort(&mut self) {
+        self.0.sort();
+    }
+
+    /// Sort the entities of the set in place using the comparison function `cmp`.
+    ///
+    /// Equivalent to [`IndexSet::sort_by`].
+    pub fn sort_by<F>(&mut self, cmp: F)
+    where
+        F: FnMut(&Entity, &Entity) -> Ordering,
+    {
+        self.0.sort_by(cmp);
+    }
+
+    /// Sort the entities of the set in place, without preserving the order of equal ones.
+    ///
+    /// Equivalent to [`IndexSet::sort_unstable`].
+    pub fn sort_unstable(&mut self) {
+        self.0.sort_unstable();
+    }
+
+    /// Sort the entities of the set in place using the comparison function `cmp`,
+    /// without preserving the order of equal ones.
+    ///
+    /// Equivalent to [`IndexSet::sort_unstable_by`].
+    pub fn sort_unstable_by<F>(&mut self, cmp: F)
+    where
+        F: FnMut(&Entity, &Entity) -> Ordering,
+    {
+        self.0.sort_unstable_by(cmp);
+    }
+
+    /// Sort the entities of the set in place using a key extraction function, calling
+    /// it only once per entity.
+    ///
+    /// Equivalent to [`IndexSet::sort_by_cached_key`].
+    pub fn sort_by_cached_key<K, F>(&mut self, sort_key: F)
+    where
+        K: Ord,
+        F: FnMut(&Entity) -> K,
+    {
+        self.0.sort_by_cached_key(sort_key);
+    }
+
+    /// Reverses the order of the entities in place.
+    ///
+    /// Equivalent to [`IndexSet::reverse`].
+    pub fn reverse(&mut self) {
+        self.0.reverse();
+    }
+
+    /// Moves the position of an entity from one index to another by shifting all
+    /// other entities in-between.
+    ///
+    /// Equivalent to [`IndexSet::move_index`].
+    ///
+    /// ***Panics*** if `from` or `to` are out of bounds.
+    #[track_caller]
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.0.move_index(from, to);
+    }
+
+    /// Swaps the position of two entities in the set.
+    ///
+    /// Equivalent to [`IndexSet::swap_indices`].
+    ///
+    /// ***Panics*** if `a` or `b` are out of bounds.
+    #[track_caller]
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.0.swap_indices(a, b);
+    }
+}
+
+impl Deref for EntityIndexSet {
+    type Target = IndexSet<Entity, EntityHash>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl MapEntities for EntityIndexSet {
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        self.0.map_entities(entity_mapper);
+    }
+}
+
+impl<'a> IntoIterator for &'a EntityIndexSet {
+    type Item = &'a Entity;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for EntityIndexSet {
+    type Item = Entity;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter(), PhantomData)
+    }
+}
+
+impl<'a> Extend<&'a Entity> for EntityIndexSet {
+    fn extend<T: IntoIterator<Item = &'a Entity>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl Extend<Entity> for EntityIndexSet {
+    fn extend<T: IntoIterator<Item = Entity>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl<const N: usize> From<[Entity; N]> for EntityIndexSet {
+    fn from(arr: [Entity; N]) -> Self {
+        Self(IndexSet::from_iter(arr))
+    }
+}
+
+impl FromIterator<Entity> for EntityIndexSet {
+    fn from_iter<I: IntoIterator<Item = Entity>>(iterable: I) -> Self {
+        Self(IndexSet::from_iter(iterable))
+    }
+}
+
+impl FromEntitySetIterator<Entity> for EntityIndexSet {
+    fn from_entity_set_iter<I: EntitySet<Item = Entity>>(set_iter: I) -> Self {
+        // The entities are known to be unique, so the set only needs to reserve once.
+        let iter = set_iter.into_iter();
+        let mut set = Self::with_capacity(iter.size_hint().0);
+        set.0.extend(iter);
+        set
+    }
+}
+
+/// A dynamically-sized slice of entities in an [`EntityIndexSet`].
+pub type Slice = UniqueEntityIndexSlice;
+
+/// An iterator over the entities of an [`EntityIndexSet`].
+///
+/// This `struct` is created by the [`EntityIndexSet::iter`] method.
+/// See its documentation for more.
+pub struct Iter<'a, S = EntityHash>(set::Iter<'a, Entity>, PhantomData<S>);
+
+impl<'a> Iter<'a> {
+    /// Returns the inner [`Iter`](set::Iter).
+    pub fn into_inner(self) -> set::Iter<'a, Entity> {
+        self.0
+    }
+}
+
+impl<'a> Deref for Iter<'a> {
+    type Target = set::Iter<'a, Entity>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+impl FusedIterator for Iter<'_> {}
+
+impl Clone for Iter<'_> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl fmt::Debug for Iter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Iter").field(&self.0).field(&self.1).finish()
+    }
+}
+
+impl Default for Iter<'_> {
+    fn default() -> Self {
+        Self(Default::default(), PhantomData)
+    }
+}
+
+// SAFETY: Iter stems from an `EntityIndexSet`, whose entities are unique since it never
+// hands out mutable access to its inner `IndexSet<Entity, EntityHash>`.
+#[allow(unsafe_code)]
+unsafe impl EntitySetIterator for Iter<'_> {}
+
+/// An owning iterator over the entities of an [`EntityIndexSet`].
+///
+/// This `struct` is created by the [`into_iter`] method on [`EntityIndexSet`]
+/// (provided by the [`IntoIterator`] trait). See its documentation for more.
+///
+/// [`into_iter`]: IntoIterator::into_iter
+pub struct IntoIter<S = EntityHash>(set::IntoIter<Entity>, PhantomData<S>);
+
+impl IntoIter {
+    /// Returns the inner [`IntoIter`](set::IntoIter).
+    pub fn into_inner(self) -> set::IntoIter<Entity> {
+        self.0
+    }
+}
+
+impl Deref for IntoIter {
+    type Target = set::IntoIter<Entity>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Iterator for IntoIter {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl FusedIterator for IntoIter {}
+
+impl Clone for IntoIter {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl fmt::Debug for IntoIter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter")
+            .field(&self.0)
+            .field(&self.1)
+            .finish()
+    }
+}
+
+impl Default for IntoIter {
+    fn default() -> Self {
+        Self(Default::default(), PhantomData)
+    }
+}
+
+// SAFETY: IntoIter stems from an `EntityIndexSet`, whose entities are unique since it never
+// hands out mutable access to its inner `IndexSet<Entity, EntityHash>`.
+#[allow(unsafe_code)]
+unsafe impl EntitySetIterator for IntoIter {}
+
+/// A draining iterator over the entities of an [`EntityIndexSet`].
+///
+/// This `struct` is created by the [`EntityIndexSet::drain`] method.
+/// See its documentation for more.
+pub struct Drain<'a, S = EntityHash>(set::Drain<'a, Entity>, PhantomData<S>);
+
+impl<'a> Drain<'a> {
+    /// Returns the inner [`Drain`](set::Drain).
+    pub fn into_inner(self) -> set::Drain<'a, Entity> {
+        self.0
+    }
+}
+
+impl<'a> Deref for Drain<'a> {
+    type Target = set::Drain<'a, Entity>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Iterator for Drain<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Drain<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl ExactSizeIterator for Drain<'_> {}
+
+impl FusedIterator for Drain<'_> {}
+
+impl fmt::Debug for Drain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Drain")
+            .field(&self.0)
+            .field(&self.1)
+            .finish()
+    }
+}
+
+// SAFETY: Drain stems from an `EntityIndexSet`, whose entities are unique since it never
+// hands out mutable access to its inner `IndexSet<Entity, EntityHash>`.
+#[allow(unsafe_code)]
+unsafe impl EntitySetIterator for Drain<'_> {}
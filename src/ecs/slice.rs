@@ -0,0 +1,469 @@
+//! [`UniqueEntityIndexSlice`] is a slice of an ordered entity collection that `bevy_ecs`
+//! knows to be unique.
+
+use crate::util::try_simplify_range;
+use crate::{map, set, Bucket, IndexMap, IndexSet};
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FusedIterator;
+use core::ops::{Index, RangeBounds};
+use core::slice;
+
+use bevy_ecs::entity::unique_array::UniqueEntityArray;
+use bevy_ecs::entity::{Entity, EntityHash, EntitySetIterator};
+
+/// A dynamically-sized slice of the entities of an [`IndexSet<Entity, EntityHash>`][IndexSet],
+/// or of the keys of an [`IndexMap<Entity, V, EntityHash>`][IndexMap].
+///
+/// Like [`set::Slice`] and [`map::Slice`], the entities of the slice are unique by
+/// construction, and references to it are an `EntitySet`, so it can be passed directly
+/// to APIs like `Query::iter_many_unique_mut`. Since [`split_at`][Self::split_at],
+/// [`get_range`][Self::get_range] and [`chunks`][Self::chunks] divide it into disjoint
+/// slices, each of them can be used for a separate mutable query.
+///
+/// This is safely created by [`EntityIndexSet::as_slice`] and
+/// [`EntityIndexMap::as_unique_entity_slice`], and their range counterparts, since these
+/// wrappers never give mutable access to their entities.
+///
+/// # Uniqueness
+///
+/// A plain `IndexSet<Entity, EntityHash>` or `IndexMap<Entity, V, EntityHash>` can have
+/// its entities modified through [`MutableValues`][crate::set::MutableValues] or
+/// [`MutableKeys`][crate::map::MutableKeys], which can make two of them equal, and mutable
+/// queries over such a slice would then alias. Their
+/// [`as_unique_entity_slice`][IndexSet::as_unique_entity_slice] methods are therefore
+/// `unsafe`.
+///
+/// [`EntityIndexSet::as_slice`]: crate::ecs::EntityIndexSet::as_slice
+/// [`EntityIndexMap::as_unique_entity_slice`]: crate::ecs::EntityIndexMap::as_unique_entity_slice
+///
+/// # Examples
+///
+/// ```
+/// use bevy_ecs::prelude::*;
+/// use indexmap::ecs::EntityIndexSet;
+///
+/// #[derive(Component)]
+/// struct Score(u32);
+///
+/// let mut world = World::new();
+/// let players: EntityIndexSet = (0..6).map(|i| world.spawn(Score(i)).id()).collect();
+///
+/// let mut scores = world.query::<&mut Score>();
+/// let mut scores = scores.query_mut(&mut world);
+/// for (bonus, team) in players.as_slice().chunks(2).enumerate() {
+///     for mut score in scores.iter_many_unique_mut(team) {
+///         score.0 += 10 * bonus as u32;
+///     }
+/// }
+///
+/// let (first, rest) = players.as_slice().split_at(1);
+/// let last = rest.get_range(4..).unwrap();
+/// assert_eq!(last.len(), 1);
+///
+/// let [mut first] = scores.get_many_unique_mut(first.to_unique_array().unwrap()).unwrap();
+/// first.0 += 1;
+/// let [last] = scores.get_many_unique(last.to_unique_array().unwrap()).unwrap();
+/// assert_eq!(last.0, 25);
+/// ```
+#[repr(transparent)]
+pub struct UniqueEntityIndexSlice<V = ()> {
+    entries: [Bucket<Entity, V>],
+}
+
+// SAFETY: `UniqueEntityIndexSlice<V>` is a transparent wrapper around `[Bucket<Entity, V>]`,
+// like `set::Slice<Entity>` and `map::Slice<Entity, V>`, and reference lifetimes are
+// bound together in function signatures.
+#[allow(unsafe_code)]
+impl<V> UniqueEntityIndexSlice<V> {
+    /// Caller must ensure that the entities of `entries` are unique.
+    unsafe fn from_entries_unchecked(entries: &[Bucket<Entity, V>]) -> &Self {
+        // SAFETY: `Self` is a transparent wrapper around `[Bucket<Entity, V>]`.
+        unsafe { &*(entries as *const [Bucket<Entity, V>] as *const Self) }
+    }
+
+    /// Converts a [`map::Slice`] to a `UniqueEntityIndexSlice`, without checking that
+    /// its keys are unique.
+    ///
+    /// # Safety
+    ///
+    /// The keys of `slice` must be unique entities. This is the case for every
+    /// [`map::Slice`] of a correctly behaving [`IndexMap`].
+    pub unsafe fn from_map_slice_unchecked(slice: &map::Slice<Entity, V>) -> &Self {
+        // SAFETY: `map::Slice<Entity, V>` is a transparent wrapper around `[Bucket<Entity, V>]`.
+        unsafe { &*(slice as *const map::Slice<Entity, V> as *const Self) }
+    }
+
+    /// Returns the slice as a [`map::Slice`].
+    pub fn as_map_slice(&self) -> &map::Slice<Entity, V> {
+        // SAFETY: `map::Slice<Entity, V>` is a transparent wrapper around `[Bucket<Entity, V>]`.
+        unsafe { &*(self as *const Self as *const map::Slice<Entity, V>) }
+    }
+
+    /// Returns the entities in the given range of indices.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    pub fn get_range<R: RangeBounds<usize>>(&self, range: R) -> Option<&Self> {
+        let range = try_simplify_range(range, self.entries.len())?;
+        let entries = self.entries.get(range)?;
+        // SAFETY: A subslice of unique entities is unique.
+        Some(unsafe { Self::from_entries_unchecked(entries) })
+    }
+
+    /// Divides one slice into two at an index.
+    ///
+    /// ***Panics*** if `index > len`.
+    pub fn split_at(&self, index: usize) -> (&Self, &Self) {
+        let (first, second) = self.entries.split_at(index);
+        // SAFETY: Both halves are subslices of unique entities, so they are unique.
+        unsafe {
+            (
+                Self::from_entries_unchecked(first),
+                Self::from_entries_unchecked(second),
+            )
+        }
+    }
+
+    /// Returns the first entity and the rest of the slice,
+    /// or `None` if it is empty.
+    pub fn split_first(&self) -> Option<(&Entity, &Self)> {
+        if let [first, rest @ ..] = &self.entries {
+            // SAFETY: `rest` is a subslice of unique entities, so it is unique.
+            Some((&first.key, unsafe { Self::from_entries_unchecked(rest) }))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the last entity and the rest of the slice,
+    /// or `None` if it is empty.
+    pub fn split_last(&self) -> Option<(&Entity, &Self)> {
+        if let [rest @ .., last] = &self.entries {
+            // SAFETY: `rest` is a subslice of unique entities, so it is unique.
+            Some((&last.key, unsafe { Self::from_entries_unchecked(rest) }))
+        } else {
+            None
+        }
+    }
+
+    /// Copies the entities of the slice to a `UniqueEntityArray`, which can be used for
+    /// queries like `Query::get_many_unique_mut`, or returns `None` if the slice
+    /// doesn't have exactly `N` entities.
+    pub fn to_unique_array<const N: usize>(&self) -> Option<UniqueEntityArray<N>> {
+        if self.entries.len() != N {
+            return None;
+        }
+        let mut entities = [Entity::PLACEHOLDER; N];
+        for (entity, bucket) in entities.iter_mut().zip(&self.entries) {
+            *entity = bucket.key;
+        }
+        // SAFETY: The entities are copied from the slice, so they are unique.
+        Some(unsafe { UniqueEntityArray::from_array_unchecked(entities) })
+    }
+}
+
+#[allow(unsafe_code)]
+impl UniqueEntityIndexSlice {
+    /// Converts a [`set::Slice`] to a `UniqueEntityIndexSlice`, without checking that
+    /// its entities are unique.
+    ///
+    /// # Safety
+    ///
+    /// The entities of `slice` must be unique. This is the case for every [`set::Slice`]
+    /// of a correctly behaving [`IndexSet`].
+    pub unsafe fn from_set_slice_unchecked(slice: &set::Slice<Entity>) -> &Self {
+        // SAFETY: `set::Slice<Entity>` is a transparent wrapper around `[Bucket<Entity, ()>]`.
+        unsafe { &*(slice as *const set::Slice<Entity> as *const Self) }
+    }
+
+    /// Returns the slice as a [`set::Slice`].
+    pub fn as_set_slice(&self) -> &set::Slice<Entity> {
+        // SAFETY: `set::Slice<Entity>` is a transparent wrapper around `[Bucket<Entity, ()>]`.
+        unsafe { &*(self as *const Self as *const set::Slice<Entity>) }
+    }
+}
+
+impl<V> UniqueEntityIndexSlice<V> {
+    /// Return the number of entities in the slice.
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the slice contains no entities.
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get an entity by index.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    pub fn get_index(&self, index: usize) -> Option<&Entity> {
+        self.entries.get(index).map(Bucket::key_ref)
+    }
+
+    /// Get the first entity.
+    pub fn first(&self) -> Option<&Entity> {
+        self.entries.first().map(Bucket::key_ref)
+    }
+
+    /// Get the last entity.
+    pub fn last(&self) -> Option<&Entity> {
+        self.entries.last().map(Bucket::key_ref)
+    }
+
+    /// Return an iterator over the entities of the slice.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter {
+            iter: self.entries.iter(),
+        }
+    }
+
+    /// Return an iterator over `chunk_size` entities of the slice at a time, starting at
+    /// the beginning of the slice.
+    ///
+    /// The chunks are slices and do not overlap. If `chunk_size` does not divide the
+    /// length of the slice, then the last chunk will not have length `chunk_size`.
+    ///
+    /// ***Panics*** if `chunk_size` is 0.
+    #[track_caller]
+    pub fn chunks(&self, chunk_size: usize) -> Chunks<'_, V> {
+        Chunks {
+            iter: self.entries.chunks(chunk_size),
+        }
+    }
+}
+
+#[allow(unsafe_code)]
+impl IndexSet<Entity, EntityHash> {
+    /// Returns a slice of all the entities in the set, that `bevy_ecs` knows to be unique.
+    ///
+    /// [`EntityIndexSet::as_slice`](crate::ecs::EntityIndexSet::as_slice) does the same
+    /// safely.
+    ///
+    /// # Safety
+    ///
+    /// No two entities of the set may have been made equal through
+    /// [`MutableValues`][crate::set::MutableValues].
+    pub unsafe fn as_unique_entity_slice(&self) -> &UniqueEntityIndexSlice {
+        // SAFETY: The entities of an `IndexSet` are unique, unless `MutableValues` made
+        // some of them equal, which the caller ensures it didn't.
+        unsafe { UniqueEntityIndexSlice::from_set_slice_unchecked(self.as_slice()) }
+    }
+
+    /// Returns a slice of the entities in the given range of indices, that `bevy_ecs`
+    /// knows to be unique.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    ///
+    /// [`EntityIndexSet::get_range`](crate::ecs::EntityIndexSet::get_range) does the same
+    /// safely.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`as_unique_entity_slice`][Self::as_unique_entity_slice].
+    pub unsafe fn get_unique_entity_range<R: RangeBounds<usize>>(
+        &self,
+        range: R,
+    ) -> Option<&UniqueEntityIndexSlice> {
+        // SAFETY: The caller upholds the requirements of `as_unique_entity_slice`.
+        unsafe { self.as_unique_entity_slice() }.get_range(range)
+    }
+}
+
+#[allow(unsafe_code)]
+impl<V> IndexMap<Entity, V, EntityHash> {
+    /// Returns a slice of all the keys in the map, that `bevy_ecs` knows to be unique.
+    ///
+    /// [`EntityIndexMap::as_unique_entity_slice`] does the same safely.
+    ///
+    /// [`EntityIndexMap::as_unique_entity_slice`]: crate::ecs::EntityIndexMap::as_unique_entity_slice
+    ///
+    /// # Safety
+    ///
+    /// No two keys of the map may have been made equal through
+    /// [`MutableKeys`][crate::map::MutableKeys] or
+    /// [`MutableEntryKey`][crate::map::MutableEntryKey].
+    pub unsafe fn as_unique_entity_slice(&self) -> &UniqueEntityIndexSlice<V> {
+        // SAFETY: The keys of an `IndexMap` are unique, unless `MutableKeys` or
+        // `MutableEntryKey` made some of them equal, which the caller ensures they didn't.
+        unsafe { UniqueEntityIndexSlice::from_map_slice_unchecked(self.as_slice()) }
+    }
+
+    /// Returns a slice of the keys in the given range of indices, that `bevy_ecs`
+    /// knows to be unique.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    ///
+    /// [`EntityIndexMap::get_unique_entity_range`] does the same safely.
+    ///
+    /// [`EntityIndexMap::get_unique_entity_range`]: crate::ecs::EntityIndexMap::get_unique_entity_range
+    ///
+    /// # Safety
+    ///
+    /// Same as [`as_unique_entity_slice`][Self::as_unique_entity_slice].
+    pub unsafe fn get_unique_entity_range<R: RangeBounds<usize>>(
+        &self,
+        range: R,
+    ) -> Option<&UniqueEntityIndexSlice<V>> {
+        // SAFETY: The caller upholds the requirements of `as_unique_entity_slice`.
+        unsafe { self.as_unique_entity_slice() }.get_range(range)
+    }
+}
+
+impl<'a, V> IntoIterator for &'a UniqueEntityIndexSlice<V> {
+    type Item = &'a Entity;
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V> Default for &UniqueEntityIndexSlice<V> {
+    fn default() -> Self {
+        #[allow(unsafe_code)]
+        // SAFETY: An empty slice has no duplicates.
+        unsafe {
+            UniqueEntityIndexSlice::from_entries_unchecked(&[])
+        }
+    }
+}
+
+impl<V> fmt::Debug for UniqueEntityIndexSlice<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<V> PartialEq for UniqueEntityIndexSlice<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other)
+    }
+}
+
+impl<V> Eq for UniqueEntityIndexSlice<V> {}
+
+impl<V> Hash for UniqueEntityIndexSlice<V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for entity in self {
+            entity.hash(state);
+        }
+    }
+}
+
+impl<V> Index<usize> for UniqueEntityIndexSlice<V> {
+    type Output = Entity;
+
+    fn index(&self, index: usize) -> &Entity {
+        &self.entries[index].key
+    }
+}
+
+/// An iterator over the entities of a [`UniqueEntityIndexSlice`].
+///
+/// This `struct` is created by the [`UniqueEntityIndexSlice::iter`] method.
+/// See its documentation for more.
+pub struct Iter<'a, V = ()> {
+    iter: slice::Iter<'a, Bucket<Entity, V>>,
+}
+
+impl<'a, V> Iter<'a, V> {
+    /// Returns a slice of the remaining entities in the iterator.
+    pub fn as_slice(&self) -> &'a UniqueEntityIndexSlice<V> {
+        #[allow(unsafe_code)]
+        // SAFETY: The remaining entities are a subslice of a `UniqueEntityIndexSlice`.
+        unsafe {
+            UniqueEntityIndexSlice::from_entries_unchecked(self.iter.as_slice())
+        }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = &'a Entity;
+
+    iterator_methods!(Bucket::key_ref);
+}
+
+impl<V> DoubleEndedIterator for Iter<'_, V> {
+    double_ended_iterator_methods!(Bucket::key_ref);
+}
+
+impl<V> ExactSizeIterator for Iter<'_, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<V> FusedIterator for Iter<'_, V> {}
+
+impl<V> Clone for Iter<'_, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<V> fmt::Debug for Iter<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<V> Default for Iter<'_, V> {
+    fn default() -> Self {
+        Self { iter: [].iter() }
+    }
+}
+
+// SAFETY: Iter stems from a `UniqueEntityIndexSlice`, whose entities are unique.
+#[allow(unsafe_code)]
+unsafe impl<V> EntitySetIterator for Iter<'_, V> {}
+
+/// An iterator over the chunks of a [`UniqueEntityIndexSlice`].
+///
+/// This `struct` is created by the [`UniqueEntityIndexSlice::chunks`] method.
+/// See its documentation for more.
+pub struct Chunks<'a, V = ()> {
+    iter: slice::Chunks<'a, Bucket<Entity, V>>,
+}
+
+#[allow(unsafe_code)]
+fn chunk<V>(entries: &[Bucket<Entity, V>]) -> &UniqueEntityIndexSlice<V> {
+    // SAFETY: The chunks are subslices of a `UniqueEntityIndexSlice`.
+    unsafe { UniqueEntityIndexSlice::from_entries_unchecked(entries) }
+}
+
+impl<'a, V> Iterator for Chunks<'a, V> {
+    type Item = &'a UniqueEntityIndexSlice<V>;
+
+    iterator_methods!(chunk);
+}
+
+impl<V> DoubleEndedIterator for Chunks<'_, V> {
+    double_ended_iterator_methods!(chunk);
+}
+
+impl<V> ExactSizeIterator for Chunks<'_, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<V> FusedIterator for Chunks<'_, V> {}
+
+impl<V> Clone for Chunks<'_, V> {
+    fn clone(&self) -> Self {
+        Chunks {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<V> fmt::Debug for Chunks<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
@@ -0,0 +1,652 @@
+//! [`TrackedIndexMap`] is an [`IndexMap`] that records when each of its entries changed.
+
+use crate::map::{self, IndexMap};
+use crate::Equivalent;
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::iter::{Enumerate, FusedIterator};
+use core::mem;
+use core::ops::{Index, Range};
+use core::slice;
+
+#[cfg(feature = "std")]
+use std::collections::hash_map::RandomState;
+
+use bevy_ecs::component::{Component, Tick};
+#[cfg(feature = "bevy_reflect")]
+use bevy_ecs::reflect::ReflectComponent;
+
+/// An [`IndexMap`] that stores a change [`Tick`] with every entry, and logs the keys it
+/// inserts and removes and the indices it reorders.
+///
+/// Change detection on a component only tells that *something* changed in it. With a
+/// `TrackedIndexMap`, a system can find out which entries changed since it last ran, with
+/// [`changed_since`][Self::changed_since], [`inserted_since`][Self::inserted_since],
+/// [`removed_since`][Self::removed_since] and [`moved_since`][Self::moved_since], and
+/// only process those.
+///
+/// Every method that modifies the map takes the current change tick, for example from
+/// `SystemChangeTick::this_run` or `World::change_tick`. The queries take the ticks of
+/// the last and the current run of the system, like [`Tick::is_newer_than`], which keeps
+/// them correct across tick wraparound as long as [`check_ticks`][Self::check_ticks]
+/// is called as regularly as bevy checks its own ticks.
+///
+/// The logs grow with every insertion, removal and move, until they are cleared with
+/// [`clear_logs`][Self::clear_logs] or aged out by `check_ticks`.
+///
+/// The map is a [`Component`], to keep per-entity state. With the `bevy_reflect` feature,
+/// it implements `Reflect` and registers `ReflectComponent`, so that it can be saved in
+/// scenes once its type is registered, for example with
+/// `App::register_type::<TrackedIndexMap<K, V>>()`.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_ecs::component::Tick;
+/// use indexmap::ecs::TrackedIndexMap;
+///
+/// let mut map = TrackedIndexMap::new();
+/// map.insert("a", 1, Tick::new(1));
+/// map.insert("b", 2, Tick::new(1));
+/// map.insert("c", 3, Tick::new(1));
+///
+/// let last_run = Tick::new(1);
+/// *map.get_mut("b", Tick::new(2)).unwrap() += 10;
+/// map.shift_remove("a", Tick::new(3));
+/// let this_run = Tick::new(4);
+///
+/// let changed: Vec<_> = map.changed_since(last_run, this_run).collect();
+/// assert_eq!(changed, [(0, &"b", &12)]);
+/// assert!(map.inserted_since(last_run, this_run).eq(&[] as &[&str]));
+/// assert!(map.removed_since(last_run, this_run).eq(&["a"]));
+/// assert!(map.moved_since(last_run, this_run).eq(&[0..2]));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Component)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Component)
+)]
+pub struct TrackedIndexMap<K, V, S = RandomState> {
+    map: IndexMap<K, Tracked<V>, S>,
+    inserted: Vec<(Tick, K)>,
+    removed: Vec<(Tick, K)>,
+    moved: Vec<(Tick, Range<usize>)>,
+}
+#[cfg(not(feature = "std"))]
+#[derive(Component)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Component)
+)]
+pub struct TrackedIndexMap<K, V, S> {
+    map: IndexMap<K, Tracked<V>, S>,
+    inserted: Vec<(Tick, K)>,
+    removed: Vec<(Tick, K)>,
+    moved: Vec<(Tick, Range<usize>)>,
+}
+
+/// A value of a [`TrackedIndexMap`], with the tick of its last change.
+#[derive(Clone)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::Reflect))]
+struct Tracked<V> {
+    value: V,
+    changed: Tick,
+}
+
+impl<K, V, S> Clone for TrackedIndexMap<K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        TrackedIndexMap {
+            map: self.map.clone(),
+            inserted: self.inserted.clone(),
+            removed: self.removed.clone(),
+            moved: self.moved.clone(),
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for TrackedIndexMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> Default for TrackedIndexMap<K, V, S>
+where
+    S: Default,
+{
+    /// Return an empty [`TrackedIndexMap`]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<K, V> TrackedIndexMap<K, V> {
+    /// Create a new map. (Does not allocate.)
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, V, S> TrackedIndexMap<K, V, S> {
+    /// Create a new map with `hash_builder`.
+    pub const fn with_hasher(hash_builder: S) -> Self {
+        TrackedIndexMap {
+            map: IndexMap::with_hasher(hash_builder),
+            inserted: Vec::new(),
+            removed: Vec::new(),
+            moved: Vec::new(),
+        }
+    }
+
+    /// Return the number of key-value pairs in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    /// Get a key-value pair by index, with the tick of its last change.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V, Tick)> {
+        let (key, tracked) = self.map.get_index(index)?;
+        Some((key, &tracked.value, tracked.changed))
+    }
+
+    /// Get a key-value pair by index, for mutation, and mark it as changed at `tick`.
+    ///
+    /// Valid indices are `0 <= index < self.len()`.
+    pub fn get_index_mut(&mut self, index: usize, tick: Tick) -> Option<(&K, &mut V)> {
+        let (key, tracked) = self.map.get_index_mut(index)?;
+        tracked.changed = tick;
+        Some((key, &mut tracked.value))
+    }
+
+    /// Return an iterator over the entries that changed since `last_run`, with their index.
+    ///
+    /// Inserted entries are changed at the tick of their insertion.
+    pub fn changed_since(&self, last_run: Tick, this_run: Tick) -> ChangedSince<'_, K, V> {
+        ChangedSince {
+            iter: self.map.as_slice().iter().enumerate(),
+            last_run,
+            this_run,
+        }
+    }
+
+    /// Return an iterator over the keys inserted since `last_run`, in the order of
+    /// their insertion.
+    ///
+    /// Keys that were removed afterwards are still included.
+    pub fn inserted_since(&self, last_run: Tick, this_run: Tick) -> LogSince<'_, K> {
+        LogSince::new(&self.inserted, last_run, this_run)
+    }
+
+    /// Return an iterator over the keys removed since `last_run`, in the order of
+    /// their removal.
+    ///
+    /// Keys that were inserted again afterwards are still included.
+    pub fn removed_since(&self, last_run: Tick, this_run: Tick) -> LogSince<'_, K> {
+        LogSince::new(&self.removed, last_run, this_run)
+    }
+
+    /// Return an iterator over the ranges of indices whose entries moved since `last_run`,
+    /// in the order of the moves.
+    ///
+    /// Moves are logged by [`move_index`][Self::move_index], [`swap_indices`][Self::swap_indices],
+    /// the sorting methods, and by the removals that shift or swap other entries. The ranges
+    /// are the indices at the time of each move, and only the indices that are still in
+    /// bounds are affected.
+    pub fn moved_since(&self, last_run: Tick, this_run: Tick) -> LogSince<'_, Range<usize>> {
+        LogSince::new(&self.moved, last_run, this_run)
+    }
+
+    /// Remove all the entries of the logs of inserted, removed and moved entries.
+    ///
+    /// The change ticks of the entries are kept.
+    pub fn clear_logs(&mut self) {
+        self.inserted.clear();
+        self.removed.clear();
+        self.moved.clear();
+    }
+
+    /// Clamps the change ticks of the entries that are older than [`Tick::MAX`] relative
+    /// to `change_tick`, and removes the logged changes that are that old.
+    ///
+    /// This should be called whenever bevy checks the ticks of its components, so that
+    /// change detection still works after the world's change tick wraps around.
+    pub fn check_ticks(&mut self, change_tick: Tick) {
+        let too_old = |tick: Tick| change_tick.get().wrapping_sub(tick.get()) > Tick::MAX.get();
+        let oldest = Tick::new(change_tick.get().wrapping_sub(Tick::MAX.get()));
+        for tracked in self.map.values_mut() {
+            if too_old(tracked.changed) {
+                tracked.changed = oldest;
+            }
+        }
+        self.inserted.retain(|&(tick, _)| !too_old(tick));
+        self.removed.retain(|&(tick, _)| !too_old(tick));
+        self.moved.retain(|&(tick, _)| !too_old(tick));
+    }
+
+    /// Moves the position of a key-value pair from one index to another
+    /// by shifting all other pairs in-between, and logs the move at `tick`.
+    ///
+    /// See [`IndexMap::move_index`].
+    ///
+    /// ***Panics*** if `from` or `to` are out of bounds.
+    #[track_caller]
+    pub fn move_index(&mut self, from: usize, to: usize, tick: Tick) {
+        self.map.move_index(from, to);
+        if from != to {
+            self.moved.push((tick, from.min(to)..from.max(to) + 1));
+        }
+    }
+
+    /// Swaps the position of two key-value pairs in the map, and logs the move at `tick`.
+    ///
+    /// See [`IndexMap::swap_indices`].
+    ///
+    /// ***Panics*** if `a` or `b` are out of bounds.
+    #[track_caller]
+    pub fn swap_indices(&mut self, a: usize, b: usize, tick: Tick) {
+        self.map.swap_indices(a, b);
+        if a != b {
+            self.moved.push((tick, a..a + 1));
+            self.moved.push((tick, b..b + 1));
+        }
+    }
+
+    /// Sort the map's key-value pairs in place using the comparison function `cmp`,
+    /// and logs the move of all of them at `tick`.
+    ///
+    /// See [`IndexMap::sort_by`].
+    pub fn sort_by<F>(&mut self, mut cmp: F, tick: Tick)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.map
+            .sort_by(|k1, t1, k2, t2| cmp(k1, &t1.value, k2, &t2.value));
+        self.log_sort(tick);
+    }
+
+    fn log_sort(&mut self, tick: Tick) {
+        if self.len() > 1 {
+            self.moved.push((tick, 0..self.len()));
+        }
+    }
+}
+
+impl<K, V, S> TrackedIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Return `true` if an equivalent to `key` exists in the map.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Return a reference to the value stored for `key`, if it is present.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.get(key).map(|tracked| &tracked.value)
+    }
+
+    /// Return item index, if it exists in the map.
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.get_index_of(key)
+    }
+
+    /// Return the tick of the last change of the value stored for `key`, if it is present.
+    pub fn get_tick<Q>(&self, key: &Q) -> Option<Tick>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        self.map.get(key).map(|tracked| tracked.changed)
+    }
+
+    /// Return a mutable reference to the value stored for `key`, if it is present,
+    /// and mark it as changed at `tick`.
+    pub fn get_mut<Q>(&mut self, key: &Q, tick: Tick) -> Option<&mut V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let tracked = self.map.get_mut(key)?;
+        tracked.changed = tick;
+        Some(&mut tracked.value)
+    }
+
+    /// Insert a key-value pair in the map, and mark it as changed at `tick`.
+    ///
+    /// If an equivalent key already exists in the map, its value is replaced and the
+    /// old value is returned. Otherwise, the new pair is inserted at the end of the map,
+    /// its key is logged as inserted, and `None` is returned.
+    ///
+    /// See [`IndexMap::insert`].
+    pub fn insert(&mut self, key: K, value: V, tick: Tick) -> Option<V>
+    where
+        K: Clone,
+    {
+        self.insert_full(key, value, tick).1
+    }
+
+    /// Insert a key-value pair in the map, and get its index, like
+    /// [`insert`][Self::insert].
+    ///
+    /// See [`IndexMap::insert_full`].
+    pub fn insert_full(&mut self, key: K, value: V, tick: Tick) -> (usize, Option<V>)
+    where
+        K: Clone,
+    {
+        let tracked = Tracked {
+            value,
+            changed: tick,
+        };
+        match self.map.get_index_of(&key) {
+            Some(index) => {
+                let old = mem::replace(&mut self.map[index], tracked);
+                (index, Some(old.value))
+            }
+            None => {
+                self.inserted.push((tick, key.clone()));
+                let (index, _) = self.map.insert_full(key, tracked);
+                (index, None)
+            }
+        }
+    }
+
+    /// Remove the key-value pair equivalent to `key` and return its value, and log the
+    /// removal at `tick`.
+    ///
+    /// Like [`Vec::remove`], the pair is removed by shifting all of the elements that
+    /// follow it, which are logged as moved.
+    ///
+    /// See [`IndexMap::shift_remove`].
+    pub fn shift_remove<Q>(&mut self, key: &Q, tick: Tick) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let (index, key, tracked) = self.map.shift_remove_full(key)?;
+        self.removed.push((tick, key));
+        if index < self.len() {
+            self.moved.push((tick, index..self.len()));
+        }
+        Some(tracked.value)
+    }
+
+    /// Remove the key-value pair equivalent to `key` and return its value, and log the
+    /// removal at `tick`.
+    ///
+    /// Like [`Vec::swap_remove`], the pair is removed by swapping it with the last
+    /// element of the map, which is logged as moved.
+    ///
+    /// See [`IndexMap::swap_remove`].
+    pub fn swap_remove<Q>(&mut self, key: &Q, tick: Tick) -> Option<V>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let (index, key, tracked) = self.map.swap_remove_full(key)?;
+        self.removed.push((tick, key));
+        if index < self.len() {
+            self.moved.push((tick, index..index + 1));
+        }
+        Some(tracked.value)
+    }
+
+    /// Remove all key-value pairs in the map, and log their removal at `tick`.
+    pub fn clear(&mut self, tick: Tick) {
+        self.removed
+            .extend(self.map.drain(..).map(|(key, _)| (tick, key)));
+    }
+
+    /// Retains only the elements specified by the predicate, and logs the removal of the
+    /// others at `tick`.
+    ///
+    /// The predicate gets a mutable reference to the values, but doesn't mark them as
+    /// changed; use [`get_index_mut`][Self::get_index_mut] for that. The elements that
+    /// follow the first removed one are logged as moved.
+    ///
+    /// See [`IndexMap::retain`].
+    pub fn retain<F>(&mut self, mut keep: F, tick: Tick)
+    where
+        K: Clone,
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let removed = &mut self.removed;
+        let mut index = 0;
+        let mut first_removed = None;
+        self.map.retain(|key, tracked| {
+            let kept = keep(key, &mut tracked.value);
+            if !kept {
+                removed.push((tick, key.clone()));
+                first_removed.get_or_insert(index);
+            }
+            index += 1;
+            kept
+        });
+        if let Some(first) = first_removed {
+            if first < self.len() {
+                self.moved.push((tick, first..self.len()));
+            }
+        }
+    }
+
+    /// Sort the map's key-value pairs by the default ordering of the keys, and logs the
+    /// move of all of them at `tick`.
+    ///
+    /// See [`IndexMap::sort_keys`].
+    pub fn sort_keys(&mut self, tick: Tick)
+    where
+        K: Ord,
+    {
+        self.map.sort_keys();
+        self.log_sort(tick);
+    }
+}
+
+impl<K, V, S, Q> Index<&Q> for TrackedIndexMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    Q: ?Sized + Hash + Equivalent<K>,
+{
+    type Output = V;
+
+    /// Returns a reference to the value corresponding to the supplied `key`.
+    ///
+    /// ***Panics*** if `key` is not present in the map.
+    fn index(&self, key: &Q) -> &V {
+        &self.map[key].value
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a TrackedIndexMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`TrackedIndexMap`].
+///
+/// This `struct` is created by the [`TrackedIndexMap::iter`] method.
+/// See its documentation for more.
+pub struct Iter<'a, K, V> {
+    iter: map::Iter<'a, K, Tracked<V>>,
+}
+
+fn untracked<'a, K, V>((key, tracked): (&'a K, &'a Tracked<V>)) -> (&'a K, &'a V) {
+    (key, &tracked.value)
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    iterator_methods!(untracked);
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    double_ended_iterator_methods!(untracked);
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the entries of a [`TrackedIndexMap`] that changed since a tick,
+/// with their index.
+///
+/// This `struct` is created by the [`TrackedIndexMap::changed_since`] method.
+/// See its documentation for more.
+pub struct ChangedSince<'a, K, V> {
+    iter: Enumerate<map::Iter<'a, K, Tracked<V>>>,
+    last_run: Tick,
+    this_run: Tick,
+}
+
+impl<'a, K, V> Iterator for ChangedSince<'a, K, V> {
+    type Item = (usize, &'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (last_run, this_run) = (self.last_run, self.this_run);
+        self.iter.find_map(|(index, (key, tracked))| {
+            if tracked.changed.is_newer_than(last_run, this_run) {
+                Some((index, key, &tracked.value))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<K, V> FusedIterator for ChangedSince<'_, K, V> {}
+
+impl<K, V> Clone for ChangedSince<'_, K, V> {
+    fn clone(&self) -> Self {
+        ChangedSince {
+            iter: self.iter.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ChangedSince<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the changes logged by a [`TrackedIndexMap`] since a tick.
+///
+/// This `struct` is created by the [`TrackedIndexMap::inserted_since`],
+/// [`TrackedIndexMap::removed_since`] and [`TrackedIndexMap::moved_since`] methods.
+/// See their documentation for more.
+pub struct LogSince<'a, T> {
+    iter: slice::Iter<'a, (Tick, T)>,
+    last_run: Tick,
+    this_run: Tick,
+}
+
+impl<'a, T> LogSince<'a, T> {
+    fn new(log: &'a [(Tick, T)], last_run: Tick, this_run: Tick) -> Self {
+        LogSince {
+            iter: log.iter(),
+            last_run,
+            this_run,
+        }
+    }
+}
+
+impl<'a, T> Iterator for LogSince<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (last_run, this_run) = (self.last_run, self.this_run);
+        self.iter.find_map(|(tick, change)| {
+            if tick.is_newer_than(last_run, this_run) {
+                Some(change)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<T> FusedIterator for LogSince<'_, T> {}
+
+impl<T> Clone for LogSince<'_, T> {
+    fn clone(&self) -> Self {
+        LogSince {
+            iter: self.iter.clone(),
+            ..*self
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LogSince<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
@@ -34,18 +34,30 @@
 //! * `serde`: Adds implementations for [`Serialize`] and [`Deserialize`]
 //!   to [`IndexMap`] and [`IndexSet`]. Alternative implementations for
 //!   (de)serializing [`IndexMap`] as an ordered sequence are available in the
-//!   [`map::serde_seq`] module.
+//!   [`map::serde_seq`] module. Reflected collections can also register
+//!   their `serde` implementations with `register_reflect_serde`.
 //! * `arbitrary`: Adds implementations for the [`arbitrary::Arbitrary`] trait
 //!   to [`IndexMap`] and [`IndexSet`].
 //! * `quickcheck`: Adds implementations for the [`quickcheck::Arbitrary`] trait
 //!   to [`IndexMap`] and [`IndexSet`].
+//! * `bevy_reflect`: Adds [`bevy_reflect`] support for [`IndexMap`] and
+//!   [`IndexSet`], along with the ordered reflection tools in the [`reflect`]
+//!   module.
+//! * `bevy_ecs`: Adds [`bevy_ecs`] integration for sets of entities, along with the
+//!   entity collections, change tracking and ordered relationship tools in the [`ecs`]
+//!   module.
+//! * `functions`: Adds `bevy_reflect` function reflection support, including the
+//!   registration of the collection methods as dynamic functions, which also
+//!   enables the `bevy_reflect` feature.
 //! * `borsh` (**deprecated**): Adds implementations for [`BorshSerialize`] and
 //!   [`BorshDeserialize`] to [`IndexMap`] and [`IndexSet`]. Due to a cyclic
 //!   dependency that arose between [`borsh`] and `indexmap`, `borsh v1.5.6`
 //!   added an `indexmap` feature that should be used instead of enabling the
 //!   feature here.
 //!
-//! _Note: only the `std` feature is enabled by default._
+//! _Note: the `std`, `bevy_reflect` and `bevy_ecs` features are enabled by default.
+//! Use `default-features = false, features = ["std"]` for the plain collections
+//! without any bevy dependency._
 //!
 //! [feature flags]: https://doc.rust-lang.org/cargo/reference/manifest.html#the-features-section
 //! [`no_std`]: #no-standard-library-targets
@@ -56,6 +68,8 @@
 //! [`borsh`]: `::borsh`
 //! [`arbitrary::Arbitrary`]: `::arbitrary::Arbitrary`
 //! [`quickcheck::Arbitrary`]: `::quickcheck::Arbitrary`
+//! [`bevy_reflect`]: `::bevy_reflect`
+//! [`bevy_ecs`]: `::bevy_ecs`
 //!
 //! ### Alternate Hashers
 //!
@@ -99,6 +113,8 @@
 //!   from the crate `twox-hash`.
 //! - Macros [`indexmap!`] and [`indexset!`] are unavailable without `std`. Use
 //!   the macros [`indexmap_with_default!`] and [`indexset_with_default!`] instead.
+//! - The `bevy_reflect` integration uses that crate's own `no_std` support,
+//!   so reflected maps and sets need a hasher that implements `TypePath`.
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
@@ -126,7 +142,14 @@ pub mod set;
 // are documented after the "normal" methods.
 #[cfg(feature = "rayon")]
 mod rayon;
-mod reflect;
+
+#[cfg(feature = "bevy_reflect")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bevy_reflect")))]
+pub mod reflect;
+
+#[cfg(feature = "bevy_ecs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bevy_ecs")))]
+pub mod ecs;
 
 pub use crate::map::IndexMap;
 pub use crate::set::IndexSet;
@@ -299,3 +322,82 @@ impl core::fmt::Display for GetDisjointMutError {
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 impl std::error::Error for GetDisjointMutError {}
+
+/// The error type returned by [`IndexMap::replace_key`] and
+/// [`RawOccupiedEntryMut::replace_key`][map::raw_entry_v1::RawOccupiedEntryMut::replace_key].
+///
+/// Either way, the key that could not be used is given back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplaceKeyError<K> {
+    /// There was no entry for the key to replace.
+    NotFound(K),
+    /// The new key is equivalent to the key of another entry.
+    Occupied {
+        /// The new key.
+        key: K,
+        /// The index of the entry that already has an equivalent key.
+        index: usize,
+    },
+}
+
+impl<K> ReplaceKeyError<K> {
+    /// Return the key that could not be used.
+    pub fn into_key(self) -> K {
+        match self {
+            ReplaceKeyError::NotFound(key) | ReplaceKeyError::Occupied { key, .. } => key,
+        }
+    }
+}
+
+impl<K> core::fmt::Display for ReplaceKeyError<K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReplaceKeyError::NotFound(_) => f.write_str("the key to replace was not found"),
+            ReplaceKeyError::Occupied { index, .. } => {
+                write!(
+                    f,
+                    "the new key is already used by the entry at index {index}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<K: core::fmt::Debug> std::error::Error for ReplaceKeyError<K> {}
+
+/// The error type returned by [`rehash_keys`][map::MutableKeys::rehash_keys] with the
+/// [`DuplicateKeys::Error`][map::DuplicateKeys::Error] policy.
+///
+/// The map kept the first entry of each key, and the duplicate entries that were
+/// removed are given back in order, each with the index of the entry kept for its key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKeysError<K, V> {
+    duplicates: Vec<(usize, K, V)>,
+}
+
+impl<K, V> DuplicateKeysError<K, V> {
+    /// Return the removed duplicates, with the index of the entry kept for each key.
+    pub fn duplicates(&self) -> &[(usize, K, V)] {
+        &self.duplicates
+    }
+
+    /// Return the removed duplicates, with the index of the entry kept for each key.
+    pub fn into_duplicates(self) -> Vec<(usize, K, V)> {
+        self.duplicates
+    }
+}
+
+impl<K, V> core::fmt::Display for DuplicateKeysError<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.duplicates.len() {
+            1 => f.write_str("found 1 duplicate key"),
+            n => write!(f, "found {n} duplicate keys"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<K: core::fmt::Debug, V: core::fmt::Debug> std::error::Error for DuplicateKeysError<K, V> {}
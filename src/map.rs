@@ -19,59 +19,14 @@ pub use self::iter::{
     Drain, IntoIter, IntoKeys, IntoValues, Iter, IterMut, IterMut2, Keys, Splice, Values, ValuesMut,
 };
 pub use self::mutable::MutableEntryKey;
-pub use self::mutable::MutableKeys;
+pub use self::mutable::{DuplicateKeys, MutableKeys};
 pub use self::slice::Slice;
-#[cfg(feature = "functions")]
-use crate::map::Return::Owned;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::args::Arg;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::args::FromArg;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::args::GetOwnership;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::args::Ownership;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::ArgError;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::IntoReturn;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::Return;
-use bevy_reflect::map_apply;
-use bevy_reflect::map_partial_eq;
-use bevy_reflect::map_try_apply;
-use bevy_reflect::ApplyError;
-use bevy_reflect::DynamicMap;
-use bevy_reflect::FromReflect;
-use bevy_reflect::Generics;
-use bevy_reflect::GetTypeRegistration;
-use bevy_reflect::Map;
-use bevy_reflect::MapInfo;
-use bevy_reflect::MapIter;
-use bevy_reflect::PartialReflect;
-use bevy_reflect::Reflect;
-use bevy_reflect::ReflectCloneError;
-use bevy_reflect::ReflectFromPtr;
-use bevy_reflect::ReflectFromReflect;
-use bevy_reflect::ReflectKind;
-use bevy_reflect::ReflectMut;
-use bevy_reflect::ReflectOwned;
-use bevy_reflect::ReflectRef;
-use bevy_reflect::TypeInfo;
-use bevy_reflect::TypeParamInfo;
-use bevy_reflect::TypeRegistration;
-use bevy_reflect::TypeRegistry;
-use bevy_reflect::Typed;
-use bevy_reflect::{FromType, MaybeTyped};
 
 #[cfg(feature = "rayon")]
 pub use crate::rayon::map as rayon;
 
-use super::reflect::impl_full_reflect;
-use super::reflect::impl_reflect_for_hashmap;
-#[cfg(feature = "functions")]
-use super::reflect::{impl_from_arg, impl_function_traits, impl_get_ownership, impl_into_return};
 use ::core::cmp::Ordering;
+use ::core::convert::Infallible;
 use ::core::fmt;
 use ::core::hash::{BuildHasher, Hash, Hasher};
 use ::core::mem;
@@ -79,17 +34,18 @@ use ::core::ops::{Index, IndexMut, RangeBounds};
 use alloc::boxed::Box;
 use alloc::vec::Vec;
 
-use crate::alloc::string::ToString;
-use bevy_reflect::utility::GenericTypeInfoCell;
-use bevy_reflect::TypePath;
-use std::borrow::Cow;
-
 #[cfg(feature = "std")]
 use std::collections::hash_map::RandomState;
 
 use self::core::IndexMapCore;
 use crate::util::{third, try_simplify_range};
-use crate::{Bucket, Entries, Equivalent, GetDisjointMutError, HashValue, TryReserveError};
+use crate::IndexSet;
+use crate::{
+    Bucket, Entries, Equivalent, GetDisjointMutError, HashValue, ReplaceKeyError, TryReserveError,
+};
+
+#[cfg(feature = "bevy_ecs")]
+use bevy_ecs::entity::{Entity, EntityMapper, MapEntities};
 
 /// A hash table where the iteration order of the key-value pairs is independent
 /// of the hash values of the keys.
@@ -135,13 +91,13 @@ use crate::{Bucket, Entries, Equivalent, GetDisjointMutError, HashValue, TryRese
 /// assert_eq!(letters.get(&'y'), None);
 /// ```
 #[cfg(feature = "std")]
-#[derive(TypePath)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::TypePath))]
 pub struct IndexMap<K, V, S = RandomState> {
     pub(crate) core: IndexMapCore<K, V>,
     hash_builder: S,
 }
 #[cfg(not(feature = "std"))]
-#[derive(TypePath)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::TypePath))]
 pub struct IndexMap<K, V, S> {
     pub(crate) core: IndexMapCore<K, V>,
     hash_builder: S,
@@ -325,6 +281,77 @@ impl<K, V, S> IndexMap<K, V, S> {
         IntoValues::new(self.into_entries())
     }
 
+    /// Convert into a map with the same keys, in the same order, and the values
+    /// returned by `f` for each key-value pair.
+    ///
+    /// The keys are not hashed again, as the new map reuses the hashes and the hash
+    /// table of this one, which makes this cheaper than collecting the mapped pairs
+    /// into a new map.
+    ///
+    /// Computes in **O(n)** time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let map = IndexMap::from([("a", 1), ("b", 2), ("c", 3)]);
+    /// let map = map.map_values(|key, value| format!("{key}{value}"));
+    /// assert_eq!(map["b"], "b2");
+    /// assert!(map.values().eq(["a1", "b2", "c3"]));
+    /// ```
+    pub fn map_values<W, F>(self, mut f: F) -> IndexMap<K, W, S>
+    where
+        F: FnMut(&K, V) -> W,
+    {
+        match self.try_map_values(|key, value| Ok::<W, Infallible>(f(key, value))) {
+            Ok(map) => map,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Convert into a map with the same keys, in the same order, and the values
+    /// returned by `f` for each key-value pair, or return the first error from `f`.
+    ///
+    /// Like [`map_values`][Self::map_values], this reuses the hashes and the hash table
+    /// of this map.
+    ///
+    /// Computes in **O(n)** time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let map = IndexMap::from([("a", "1"), ("b", "2")]);
+    /// let map = map.try_map_values(|_, value| value.parse::<i32>()).unwrap();
+    /// assert_eq!(map["b"], 2);
+    ///
+    /// let map = IndexMap::from([("a", "1"), ("b", "two")]);
+    /// assert!(map.try_map_values(|_, value| value.parse::<i32>()).is_err());
+    /// ```
+    pub fn try_map_values<W, E, F>(self, f: F) -> Result<IndexMap<K, W, S>, E>
+    where
+        F: FnMut(&K, V) -> Result<W, E>,
+    {
+        Ok(IndexMap {
+            core: self.core.try_map_values(f)?,
+            hash_builder: self.hash_builder,
+        })
+    }
+
+    /// Convert into an [`IndexSet`] of the keys, in the same order, dropping the values.
+    ///
+    /// Like [`map_values`][Self::map_values], this reuses the hashes and the hash table
+    /// of this map.
+    ///
+    /// Computes in **O(n)** time.
+    pub fn into_key_set(self) -> IndexSet<K, S> {
+        IndexSet {
+            map: self.map_values(|_, _| ()),
+        }
+    }
+
     /// Remove all key-value pairs in the map, while preserving its capacity.
     ///
     /// Computes in **O(n)** time.
@@ -668,6 +695,50 @@ where
         self.core.entry(hash, key)
     }
 
+    /// Replace the key of the entry equivalent to `old` with `new`, keeping the
+    /// entry's value and its position in the map, and return its index.
+    ///
+    /// The entry is rehashed for the new key in place, so unlike removing it and
+    /// inserting it again with [`shift_insert`][Self::shift_insert], the other entries
+    /// don't move. If `new` is equivalent to the current key, it simply replaces it.
+    ///
+    /// Returns [`ReplaceKeyError::NotFound`] if there is no entry for `old`, and
+    /// [`ReplaceKeyError::Occupied`] if `new` is equivalent to the key of another entry,
+    /// giving back `new` either way and leaving the map unchanged.
+    ///
+    /// [`Entry`] doesn't have this method, since it doesn't have the map's hasher to hash
+    /// `new` with. The raw entry API has it, as
+    /// [`RawOccupiedEntryMut::replace_key`][raw_entry_v1::RawOccupiedEntryMut::replace_key].
+    ///
+    /// Computes in **O(1)** time (average).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::{IndexMap, ReplaceKeyError};
+    ///
+    /// let mut map = IndexMap::from([("a", 1), ("b", 2), ("c", 3)]);
+    /// assert_eq!(map.replace_key("b", "B"), Ok(1));
+    /// assert!(map.keys().eq(&["a", "B", "c"]));
+    ///
+    /// assert_eq!(map.replace_key("a", "c"), Err(ReplaceKeyError::Occupied { key: "c", index: 2 }));
+    /// assert_eq!(map.replace_key("b", "x"), Err(ReplaceKeyError::NotFound("x")));
+    /// ```
+    pub fn replace_key<Q>(&mut self, old: &Q, new: K) -> Result<usize, ReplaceKeyError<K>>
+    where
+        Q: ?Sized + Hash + Equivalent<K>,
+    {
+        let index = match self.get_index_of(old) {
+            Some(index) => index,
+            None => return Err(ReplaceKeyError::NotFound(new)),
+        };
+        let hash = self.hash(&new);
+        match self.core.replace_key(index, hash, new) {
+            Ok(_) => Ok(index),
+            Err((index, key)) => Err(ReplaceKeyError::Occupied { key, index }),
+        }
+    }
+
     /// Creates a splicing iterator that replaces the specified range in the map
     /// with the given `replace_with` key-value iterator and yields the removed
     /// items. `replace_with` does not need to be the same length as `range`.
@@ -735,6 +806,52 @@ where
     pub fn append<S2>(&mut self, other: &mut IndexMap<K, V, S2>) {
         self.extend(other.drain(..));
     }
+
+    /// Moves all key-value pairs from `other` into `self`, leaving `other` empty,
+    /// without hashing the keys again.
+    ///
+    /// This is equivalent to [`append`][Self::append], but it reuses the hashes that
+    /// `other` stores for its keys, so the hasher of `other` **must** hash keys exactly
+    /// like the hasher of `self`, for example because it is a clone of it or because
+    /// the hasher type is deterministic. Otherwise the moved keys can no longer be
+    /// looked up, which is sound (memory safe) but a logical error hazard, just like
+    /// modifying keys through [`MutableKeys`]. In debug builds, this is checked by
+    /// hashing the keys again, and ***panics*** if a hash doesn't match.
+    ///
+    /// Computes in **O(n)** time (average), where n is the length of `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut a = IndexMap::from([(3, "c"), (2, "b"), (1, "a")]);
+    /// let mut b = IndexMap::with_hasher(a.hasher().clone());
+    /// b.extend([(3, "d"), (4, "e"), (5, "f")]);
+    ///
+    /// a.append_with_hashes(&mut b);
+    ///
+    /// assert!(b.is_empty());
+    /// assert!(a.keys().eq(&[3, 2, 1, 4, 5]));
+    /// assert_eq!(a[&3], "d"); // "c" was overwritten.
+    /// ```
+    pub fn append_with_hashes(&mut self, other: &mut IndexMap<K, V, S>) {
+        // Like `extend`, reserve half of the entries if some keys may already be present.
+        let reserve = if self.is_empty() {
+            other.len()
+        } else {
+            (other.len() + 1) / 2
+        };
+        self.reserve(reserve);
+        for Bucket { hash, key, value } in other.core.drain(..) {
+            debug_assert_eq!(
+                self.hash(&key),
+                hash,
+                "the maps' hashers must hash keys identically"
+            );
+            self.core.insert_full(hash, key, value);
+        }
+    }
 }
 
 impl<K, V, S> IndexMap<K, V, S>
@@ -1703,16 +1820,46 @@ where
 {
 }
 
-impl_reflect_for_hashmap!(IndexMap<K, V, S>);
-#[cfg(feature = "functions")]
-impl_function_traits!(IndexMap<K, V, S>;
-        <
-
-        K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
-
-        V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
-
-        S: TypePath + BuildHasher + Default + Send + Sync
+#[cfg(feature = "bevy_ecs")]
+impl<V, S> IndexMap<Entity, V, S>
+where
+    S: BuildHasher,
+{
+    /// Maps every key of the map, keeping the order of the entries.
+    ///
+    /// If several keys are mapped to the same entity, only the first of their entries
+    /// is kept. The values are left untouched, which makes this usable for values that
+    /// don't implement [`MapEntities`].
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn map_entity_keys<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        // The mapped keys have new hashes, so the map is rebuilt in its original order.
+        let entries: Vec<(Entity, V)> = self.drain(..).collect();
+        for (key, value) in entries {
+            self.entry(entity_mapper.get_mapped(key)).or_insert(value);
+        }
+    }
+}
 
-    >
-);
+/// Maps every key and value of the map, keeping the order of the entries.
+///
+/// If several keys are mapped to the same entity, only the first of their entries is kept.
+/// See [`map_entity_keys`][IndexMap::map_entity_keys] for values without entities.
+///
+/// Maps are meant to be mapped by the `MapEntities` of the component that holds them,
+/// like with `#[entities]`. `ReflectMapEntities` isn't registered for them, because it
+/// applies the mapped copy with the merge of reflected maps, which would keep the
+/// entries of the unmapped entities.
+#[cfg(feature = "bevy_ecs")]
+impl<V, S> MapEntities for IndexMap<Entity, V, S>
+where
+    V: MapEntities,
+    S: BuildHasher,
+{
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        for value in self.values_mut() {
+            value.map_entities(entity_mapper);
+        }
+        self.map_entity_keys(entity_mapper);
+    }
+}
@@ -183,6 +183,26 @@ impl<K, V> IndexMapCore<K, V> {
         }
     }
 
+    /// Replace every value with the result of `f`, keeping the entries' hashes and
+    /// moving the indices over as they are.
+    pub(crate) fn try_map_values<W, E, F>(self, mut f: F) -> Result<IndexMapCore<K, W>, E>
+    where
+        F: FnMut(&K, V) -> Result<W, E>,
+    {
+        let entries = self
+            .entries
+            .into_iter()
+            .map(|Bucket { hash, key, value }| {
+                let value = f(&key, value)?;
+                Ok(Bucket { hash, key, value })
+            })
+            .collect::<Result<Entries<K, W>, E>>()?;
+        Ok(IndexMapCore {
+            indices: self.indices,
+            entries,
+        })
+    }
+
     #[track_caller]
     pub(crate) fn drain<R>(&mut self, range: R) -> vec::Drain<'_, Bucket<K, V>>
     where
@@ -388,6 +408,20 @@ impl<K, V> IndexMapCore<K, V> {
         }
     }
 
+    /// Replace the key at `index` with `key`, unless it's equivalent to the key of
+    /// another entry, and return the old key -- otherwise the index of that entry.
+    pub(crate) fn replace_key(
+        &mut self,
+        index: usize,
+        hash: HashValue,
+        key: K,
+    ) -> Result<K, (usize, K)>
+    where
+        K: Eq,
+    {
+        self.borrow_mut().replace_key(index, hash, key)
+    }
+
     /// Remove an entry by shifting all entries that follow it
     pub(crate) fn shift_remove_full<Q>(&mut self, hash: HashValue, key: &Q) -> Option<(usize, K, V)>
     where
@@ -510,6 +544,61 @@ impl<K, V> IndexMapCore<K, V> {
         insert_bulk_no_grow(&mut self.indices, &self.entries);
     }
 
+    /// Recompute the hash of every entry and rebuild the indices, removing the entries
+    /// with a key equivalent to that of an earlier entry -- or a later one if `keep_last`.
+    ///
+    /// The removed entries are returned in order, each with the new index of the entry
+    /// that was kept for its key.
+    pub(crate) fn rehash_keys<F>(&mut self, mut hash: F, keep_last: bool) -> Vec<(usize, K, V)>
+    where
+        K: Eq,
+        F: FnMut(&K) -> HashValue,
+    {
+        for entry in &mut self.entries {
+            entry.hash = hash(&entry.key);
+        }
+        self.indices.clear();
+
+        // Pairs of a duplicate's index and the index of the entry kept for its key.
+        let mut duplicates = Vec::new();
+        let len = self.entries.len();
+        let (mut forward, mut backward) = (0..len, (0..len).rev());
+        let order: &mut dyn Iterator<Item = usize> = if keep_last {
+            &mut backward
+        } else {
+            &mut forward
+        };
+        for i in order {
+            let entry = &self.entries[i];
+            let eq = equivalent(&entry.key, &self.entries);
+            match self.indices.find(entry.hash.get(), eq) {
+                Some(&kept) => duplicates.push((i, kept)),
+                None => {
+                    let hasher = get_hash(&self.entries);
+                    self.indices.insert_unique(entry.hash.get(), i, hasher);
+                }
+            }
+        }
+        if duplicates.is_empty() {
+            return Vec::new();
+        }
+
+        duplicates.sort_unstable();
+        let shifted = |kept: usize| kept - duplicates.partition_point(|&(i, _)| i < kept);
+        let mut removed = Vec::with_capacity(duplicates.len());
+        let mut next = duplicates.iter().peekable();
+        let capacity = self.entries.capacity();
+        let entries = mem::replace(&mut self.entries, Vec::with_capacity(capacity));
+        for (i, entry) in entries.into_iter().enumerate() {
+            match next.next_if(|&&(dup, _)| dup == i) {
+                Some(&(_, kept)) => removed.push((shifted(kept), entry.key, entry.value)),
+                None => self.entries.push(entry),
+            }
+        }
+        self.rebuild_hash_table();
+        removed
+    }
+
     pub(crate) fn reverse(&mut self) {
         self.entries.reverse();
 
@@ -535,6 +624,29 @@ fn reserve_entries<K, V>(entries: &mut Entries<K, V>, additional: usize, try_cap
 }
 
 impl<'a, K, V> RefMut<'a, K, V> {
+    /// Replace the key at `index` with `key`, unless it's equivalent to the key of
+    /// another entry, and return the old key -- otherwise the index of that entry.
+    ///
+    /// The index is moved to the new hash in `indices` without touching the other entries.
+    fn replace_key(&mut self, index: usize, hash: HashValue, key: K) -> Result<K, (usize, K)>
+    where
+        K: Eq,
+    {
+        let eq = equivalent(&key, self.entries);
+        match self.indices.find(hash.get(), eq) {
+            Some(&i) if i != index => return Err((i, key)),
+            // An equivalent key has the same hash, so the index stays in place.
+            Some(_) => {}
+            None => {
+                erase_index(self.indices, self.entries[index].hash, index);
+                self.entries[index].hash = hash;
+                self.indices
+                    .insert_unique(hash.get(), index, get_hash(self.entries));
+            }
+        }
+        Ok(mem::replace(&mut self.entries[index].key, key))
+    }
+
     #[inline]
     fn new(indices: &'a mut Indices, entries: &'a mut Entries<K, V>) -> Self {
         Self { indices, entries }
@@ -176,6 +176,18 @@ impl<'a, K, V> OccupiedEntry<'a, K, V> {
         &self.entries[self.index()].key
     }
 
+    /// Gets a reference to the key at `index` in the map, if it is in bounds.
+    #[cfg(feature = "bevy_ecs")]
+    pub(crate) fn key_at(&self, index: usize) -> Option<&K> {
+        self.entries.get(index).map(|bucket| &bucket.key)
+    }
+
+    /// Return the number of key-value pairs in the map.
+    #[cfg(feature = "bevy_ecs")]
+    pub(crate) fn map_len(&self) -> usize {
+        self.entries.len()
+    }
+
     pub(crate) fn key_mut(&mut self) -> &mut K {
         let index = self.index();
         &mut self.entries[index].key
@@ -396,8 +408,7 @@ impl<'a, K, V> VacantEntry<'a, K, V> {
     where
         K: Ord,
     {
-        let slice = crate::map::Slice::from_slice(self.map.entries);
-        let i = slice.binary_search_keys(&self.key).unwrap_err();
+        let i = self.sorted_index();
         (i, self.shift_insert(i, value))
     }
 
@@ -412,6 +423,28 @@ impl<'a, K, V> VacantEntry<'a, K, V> {
             .shift_insert_unique(index, self.hash, self.key, value);
         &mut self.map.entries[index].value
     }
+
+    /// Inserts the entry's key and the given value into the map at the given index,
+    /// shifting others to the right, and returns an `OccupiedEntry`.
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    pub(crate) fn shift_insert_entry(mut self, index: usize, value: V) -> OccupiedEntry<'a, K, V> {
+        self.map
+            .shift_insert_unique(index, self.hash, self.key, value);
+        OccupiedEntry::from(IndexedEntry {
+            map: self.map,
+            index,
+        })
+    }
+
+    /// Returns the index where the entry's key would be inserted among sorted keys.
+    pub(crate) fn sorted_index(&self) -> usize
+    where
+        K: Ord,
+    {
+        let slice = crate::map::Slice::from_slice(self.map.entries);
+        slice.binary_search_keys(&self.key).unwrap_err()
+    }
 }
 
 impl<K: fmt::Debug, V> fmt::Debug for VacantEntry<'_, K, V> {
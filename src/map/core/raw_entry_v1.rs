@@ -10,10 +10,9 @@
 //! `IndexMap` without such an opt-in trait.
 
 use super::{Entries, RefMut};
-use crate::{Equivalent, HashValue, IndexMap};
+use crate::{Equivalent, HashValue, IndexMap, ReplaceKeyError};
 use core::fmt;
 use core::hash::{BuildHasher, Hash, Hasher};
-use core::marker::PhantomData;
 use core::mem;
 use hashbrown::hash_table;
 
@@ -293,7 +292,7 @@ impl<'a, K, V, S> RawEntryBuilderMut<'a, K, V, S> {
             Ok(index) => RawEntryMut::Occupied(RawOccupiedEntryMut {
                 entries: &mut self.map.core.entries,
                 index,
-                hash_builder: PhantomData,
+                hash_builder: &self.map.hash_builder,
             }),
             Err(absent) => RawEntryMut::Vacant(RawVacantEntryMut {
                 map: RefMut::new(absent.into_table(), &mut self.map.core.entries),
@@ -381,7 +380,7 @@ impl<'a, K, V, S> RawEntryMut<'a, K, V, S> {
 pub struct RawOccupiedEntryMut<'a, K, V, S> {
     entries: &'a mut Entries<K, V>,
     index: hash_table::OccupiedEntry<'a, usize>,
-    hash_builder: PhantomData<&'a S>,
+    hash_builder: &'a S,
 }
 
 impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for RawOccupiedEntryMut<'_, K, V, S> {
@@ -484,6 +483,30 @@ impl<'a, K, V, S> RawOccupiedEntryMut<'a, K, V, S> {
         mem::replace(self.key_mut(), key)
     }
 
+    /// Replace the key of the entry with `key`, rehashing it in place so the entry
+    /// keeps its value and its position in the map, and return the old key.
+    ///
+    /// Unlike [`insert_key`][Self::insert_key], the new key doesn't need to be
+    /// equivalent to the old one. See [`IndexMap::replace_key`] for more details.
+    ///
+    /// Returns [`ReplaceKeyError::Occupied`] if `key` is equivalent to the key of
+    /// another entry, leaving the map unchanged.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn replace_key(self, key: K) -> Result<K, ReplaceKeyError<K>>
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let mut h = self.hash_builder.build_hasher();
+        key.hash(&mut h);
+        let hash = HashValue(h.finish() as usize);
+        let index = self.index();
+        self.into_ref_mut()
+            .replace_key(index, hash, key)
+            .map_err(|(index, key)| ReplaceKeyError::Occupied { key, index })
+    }
+
     /// Remove the key, value pair stored in the map for this entry, and return the value.
     ///
     /// **NOTE:** This is equivalent to [`.swap_remove()`][Self::swap_remove], replacing this
@@ -1,9 +1,10 @@
-use core::hash::{BuildHasher, Hash};
+use core::hash::{BuildHasher, Hash, Hasher};
 
 use super::{
     Bucket, Entries, Entry, Equivalent, IndexMap, IndexedEntry, IterMut2, OccupiedEntry,
     VacantEntry,
 };
+use crate::{DuplicateKeysError, HashValue};
 
 /// Opt-in mutable access to [`IndexMap`] keys.
 ///
@@ -15,6 +16,8 @@ use super::{
 /// If keys are modified erroneously, you can no longer look them up.
 /// This is sound (memory safe) but a logical error hazard (just like
 /// implementing `PartialEq`, `Eq`, or `Hash` incorrectly would be).
+/// To change keys freely, call [`rehash_keys`][Self::rehash_keys] after
+/// modifying them.
 ///
 /// `use` this trait to enable its methods for `IndexMap`.
 ///
@@ -50,6 +53,51 @@ pub trait MutableKeys: private::Sealed {
     fn retain2<F>(&mut self, keep: F)
     where
         F: FnMut(&mut Self::Key, &mut Self::Value) -> bool;
+
+    /// Recompute the hashes of all keys and rebuild the map's hash table, so that keys
+    /// can be looked up again after changing their hash or equality through the other
+    /// methods of this trait.
+    ///
+    /// If some keys have become equivalent, all but one entry of each such key are
+    /// removed as chosen by `policy`, shifting the remaining entries to fill the gaps.
+    ///
+    /// Computes in **O(n)** time (average).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::map::{DuplicateKeys, MutableKeys};
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut map = IndexMap::from([(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+    /// map.iter_mut2().for_each(|(key, _)| *key /= 2);
+    /// map.rehash_keys(DuplicateKeys::KeepLast).unwrap();
+    /// assert!(map.into_iter().eq([(0, 'a'), (1, 'c'), (2, 'd')]));
+    ///
+    /// let mut map = IndexMap::from([(1, 'a'), (2, 'b'), (3, 'c')]);
+    /// map.iter_mut2().for_each(|(key, _)| *key %= 2);
+    /// let error = map.rehash_keys(DuplicateKeys::Error).unwrap_err();
+    /// assert_eq!(error.duplicates(), &[(0, 1, 'c')]);
+    /// assert!(map.into_iter().eq([(1, 'a'), (0, 'b')]));
+    /// ```
+    fn rehash_keys(
+        &mut self,
+        policy: DuplicateKeys,
+    ) -> Result<(), DuplicateKeysError<Self::Key, Self::Value>>
+    where
+        Self::Key: Hash + Eq;
+}
+
+/// How [`MutableKeys::rehash_keys`] handles entries with equivalent keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DuplicateKeys {
+    /// Keep the first of the entries with equivalent keys.
+    KeepFirst,
+    /// Keep the last of the entries with equivalent keys.
+    KeepLast,
+    /// Keep the first of the entries with equivalent keys,
+    /// and return the others in a [`DuplicateKeysError`].
+    Error,
 }
 
 /// Opt-in mutable access to [`IndexMap`] keys.
@@ -88,6 +136,26 @@ where
     {
         self.core.retain_in_order(keep);
     }
+
+    fn rehash_keys(&mut self, policy: DuplicateKeys) -> Result<(), DuplicateKeysError<K, V>>
+    where
+        K: Hash + Eq,
+    {
+        let hash_builder = &self.hash_builder;
+        let hash = |key: &K| {
+            let mut h = hash_builder.build_hasher();
+            key.hash(&mut h);
+            HashValue(h.finish() as usize)
+        };
+        let duplicates = self
+            .core
+            .rehash_keys(hash, policy == DuplicateKeys::KeepLast);
+        if policy == DuplicateKeys::Error && !duplicates.is_empty() {
+            Err(DuplicateKeysError { duplicates })
+        } else {
+            Ok(())
+        }
+    }
 }
 
 /// Opt-in mutable access to [`Entry`] keys.
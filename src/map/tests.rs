@@ -458,6 +458,160 @@ fn get_index_entry() {
     }
 }
 
+#[test]
+fn replace_key() {
+    let mut map: IndexMap<i32, i32> = (0..100).map(|i| (i, -i)).collect();
+
+    for i in 0..100 {
+        assert_eq!(map.replace_key(&i, i + 1000), Ok(i as usize));
+    }
+    assert_eq!(map.len(), 100);
+    for i in 0..100 {
+        assert_eq!(map.get_index_of(&i), None);
+        assert_eq!(
+            map.get_full(&(i + 1000)),
+            Some((i as usize, &(i + 1000), &-i))
+        );
+    }
+
+    // An equivalent key replaces the old one in place.
+    assert_eq!(map.replace_key(&1050, 1050), Ok(50));
+    assert_eq!(map.get_index_of(&1050), Some(50));
+
+    assert_eq!(
+        map.replace_key(&1000, 1099),
+        Err(ReplaceKeyError::Occupied {
+            key: 1099,
+            index: 99
+        })
+    );
+    assert_eq!(map.replace_key(&0, 0), Err(ReplaceKeyError::NotFound(0)));
+    assert_eq!(map.replace_key(&0, 0).unwrap_err().into_key(), 0);
+    assert!(map.keys().copied().eq(1000..1100));
+}
+
+#[test]
+fn raw_entry_replace_key() {
+    use crate::map::raw_entry_v1::{RawEntryApiV1, RawEntryMut};
+
+    let mut map: IndexMap<&str, i32> = IndexMap::from([("a", 1), ("b", 2), ("c", 3)]);
+
+    match map.raw_entry_mut_v1().from_key("b") {
+        RawEntryMut::Occupied(entry) => assert_eq!(entry.replace_key("B"), Ok("b")),
+        RawEntryMut::Vacant(_) => panic!(),
+    }
+    assert!(map.iter().eq([(&"a", &1), (&"B", &2), (&"c", &3)]));
+    assert!(!map.contains_key("b"));
+
+    match map.raw_entry_mut_v1().from_key("a") {
+        RawEntryMut::Occupied(entry) => assert_eq!(
+            entry.replace_key("c"),
+            Err(ReplaceKeyError::Occupied { key: "c", index: 2 })
+        ),
+        RawEntryMut::Vacant(_) => panic!(),
+    }
+    assert!(map.iter().eq([(&"a", &1), (&"B", &2), (&"c", &3)]));
+}
+
+#[test]
+fn rehash_keys() {
+    let new_map = || -> IndexMap<i32, i32> { (0..100).map(|i| (i, i)).collect() };
+
+    let mut map = new_map();
+    map.iter_mut2().for_each(|(key, _)| *key += 1000);
+    assert_eq!(map.get(&1000), None);
+    assert_eq!(map.rehash_keys(DuplicateKeys::Error), Ok(()));
+    assert!(map.keys().copied().eq(1000..1100));
+    for i in 0..100 {
+        assert_eq!(
+            map.get_full(&(i + 1000)),
+            Some((i as usize, &(i + 1000), &i))
+        );
+    }
+
+    let mut map = new_map();
+    map.iter_mut2().for_each(|(key, _)| *key %= 10);
+    assert_eq!(map.rehash_keys(DuplicateKeys::KeepFirst), Ok(()));
+    assert!(map
+        .iter()
+        .map(|(&k, &v)| (k, v))
+        .eq((0..10).map(|i| (i, i))));
+
+    let mut map = new_map();
+    map.iter_mut2().for_each(|(key, _)| *key %= 10);
+    assert_eq!(map.rehash_keys(DuplicateKeys::KeepLast), Ok(()));
+    assert!(map
+        .iter()
+        .map(|(&k, &v)| (k, v))
+        .eq((0..10).map(|i| (i, i + 90))));
+    assert_eq!(map.get(&3), Some(&93));
+
+    let mut map = new_map();
+    map.iter_mut2().for_each(|(key, _)| *key /= 10);
+    let error = map.rehash_keys(DuplicateKeys::Error).unwrap_err();
+    assert!(map
+        .iter()
+        .map(|(&k, &v)| (k, v))
+        .eq((0..10).map(|i| (i, i * 10))));
+    let expected: Vec<_> = (0..100)
+        .filter(|i| i % 10 != 0)
+        .map(|i| (i as usize / 10, i / 10, i))
+        .collect();
+    assert_eq!(error.into_duplicates(), expected);
+    assert_eq!(map.get_index_of(&7), Some(7));
+}
+
+#[test]
+fn map_values() {
+    let map: IndexMap<i32, i32> = (0..100).map(|i| (i * 3, i)).collect();
+
+    let sums = map.clone().map_values(|&key, value| key + value);
+    assert!(sums.keys().eq(map.keys()));
+    for (i, (key, value)) in map.iter().enumerate() {
+        assert_eq!(sums.get_full(key), Some((i, key, &(key + value))));
+    }
+    assert_eq!(sums.get(&1), None);
+
+    let result: Result<IndexMap<i32, u8>, _> = map
+        .clone()
+        .try_map_values(|_, value| u8::try_from(value * 2));
+    let bytes = result.unwrap();
+    assert_eq!(bytes.get(&99), Some(&66));
+    assert!(map
+        .clone()
+        .try_map_values(|_, value| u8::try_from(value * 3))
+        .is_err());
+
+    let set = map.clone().into_key_set();
+    assert!(set.iter().eq(map.keys()));
+    assert_eq!(set.get_index_of(&150), Some(50));
+    assert!(!set.contains(&151));
+}
+
+#[test]
+fn append_with_hashes() {
+    let mut a: IndexMap<i32, i32> = (0..100).map(|i| (i, i)).collect();
+    let mut b = IndexMap::with_hasher(a.hasher().clone());
+    b.extend((50..150).map(|i| (i, -i)));
+
+    a.append_with_hashes(&mut b);
+    assert!(b.is_empty());
+    assert!(a.keys().copied().eq(0..150));
+    for i in 0..150 {
+        let value = if i < 50 { i } else { -i };
+        assert_eq!(a.get_full(&i), Some((i as usize, &i, &value)));
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "hashers must hash keys identically"]
+fn append_with_hashes_mismatch() {
+    let mut a: IndexMap<i32, i32> = IndexMap::new();
+    let mut b: IndexMap<i32, i32> = (0..10).map(|i| (i, i)).collect();
+    a.append_with_hashes(&mut b);
+}
+
 #[test]
 fn from_entries() {
     let mut map = IndexMap::from([(1, "1"), (2, "2"), (3, "3")]);
@@ -1006,3 +1160,334 @@ fn disjoint_indices_mut_fail_duplicate() {
         Err(crate::GetDisjointMutError::OverlappingIndices)
     );
 }
+
+#[test]
+#[cfg(feature = "bevy_ecs")]
+fn map_entities_keeps_order() {
+    use bevy_ecs::entity::hash_map::EntityHashMap;
+    use bevy_ecs::entity::EntityHash;
+
+    let e = |index| Entity::from_raw(index);
+    let mut mapper: EntityHashMap<Entity> = [(e(1), e(11)), (e(2), e(12)), (e(3), e(11))]
+        .into_iter()
+        .collect();
+
+    let mut map: IndexMap<Entity, Option<Entity>, EntityHash> = [
+        (e(3), Some(e(2))),
+        (e(4), None),
+        (e(2), Some(e(5))),
+        (e(1), None),
+    ]
+    .into_iter()
+    .collect();
+    map.map_entities(&mut mapper);
+    // Both 3 and 1 are mapped to 11, so the entry of 3 is kept, at its position.
+    assert!(map
+        .into_iter()
+        .eq([(e(11), Some(e(12))), (e(4), None), (e(12), Some(e(5)))]));
+
+    let mut keys_only: IndexMap<Entity, u32, EntityHash> =
+        [(e(4), 0), (e(2), 1), (e(1), 2)].into_iter().collect();
+    keys_only.map_entity_keys(&mut mapper);
+    assert!(keys_only
+        .into_iter()
+        .eq([(e(4), 0), (e(12), 1), (e(11), 2)]));
+}
+
+#[test]
+#[cfg(feature = "bevy_ecs")]
+fn tracked_index_map() {
+    use crate::ecs::TrackedIndexMap;
+    use bevy_ecs::component::Tick;
+
+    let t = Tick::new;
+    let mut map = TrackedIndexMap::new();
+    for (i, key) in ["a", "b", "c", "d", "e"].into_iter().enumerate() {
+        map.insert(key, i, t(1));
+    }
+    fn changed_keys(
+        map: &TrackedIndexMap<&'static str, usize>,
+        last_run: u32,
+    ) -> Vec<(usize, &'static str)> {
+        map.changed_since(Tick::new(last_run), Tick::new(10))
+            .map(|(index, key, _)| (index, *key))
+            .collect()
+    }
+    assert_eq!(changed_keys(&map, 0).len(), 5);
+    assert!(changed_keys(&map, 1).is_empty());
+
+    // Replacing a value changes it without logging an insertion.
+    assert_eq!(map.insert("b", 10, t(2)), Some(1));
+    *map.get_index_mut(3, t(2)).unwrap().1 += 10;
+    assert_eq!(map.get_mut("x", t(2)), None);
+    assert_eq!(changed_keys(&map, 1), [(1, "b"), (3, "d")]);
+    assert_eq!(map.inserted_since(t(0), t(10)).count(), 5);
+    assert_eq!(map.inserted_since(t(1), t(10)).count(), 0);
+    assert_eq!(map.get_tick("d"), Some(t(2)));
+
+    assert_eq!(map.swap_remove("a", t(3)), Some(0));
+    map.retain(|_, value| *value != 2, t(3));
+    assert!(map.iter().eq([(&"e", &4), (&"b", &10), (&"d", &13)]));
+    assert!(map.removed_since(t(2), t(10)).eq(&["a", "c"]));
+    assert!(map.moved_since(t(2), t(10)).eq(&[0..1, 2..3]));
+
+    map.move_index(0, 2, t(4));
+    map.swap_indices(1, 1, t(4));
+    map.sort_keys(t(5));
+    assert!(map.moved_since(t(3), t(10)).eq(&[0..3, 0..3]));
+    assert_eq!(changed_keys(&map, 2), []);
+    assert_eq!(changed_keys(&map, 1), [(0, "b"), (1, "d")]);
+
+    // Ticks older than `Tick::MAX` are clamped, and their logs are dropped.
+    let now = t(Tick::MAX.get() + 3);
+    map.check_ticks(now);
+    assert_eq!(map.get_tick("e"), Some(t(3)));
+    assert_eq!(map.get_tick("b"), Some(t(3)));
+    assert_eq!(map.inserted_since(t(0), now).count(), 0);
+    assert!(map.moved_since(t(0), now).eq(&[0..3, 0..3]));
+
+    map.clear(t(6));
+    assert!(map.is_empty());
+    assert!(map.removed_since(t(5), t(10)).eq(&["b", "d", "e"]));
+    map.clear_logs();
+    assert_eq!(map.removed_since(t(0), t(10)).count(), 0);
+}
+
+#[test]
+#[cfg(all(feature = "bevy_ecs", feature = "bevy_reflect"))]
+fn tracked_index_map_component_reflect() {
+    use crate::ecs::TrackedIndexMap;
+    use bevy_ecs::component::Tick;
+    use bevy_ecs::prelude::*;
+    use bevy_ecs::reflect::{AppTypeRegistry, ReflectComponent};
+
+    type Tracked = TrackedIndexMap<u32, u32>;
+
+    let registry = AppTypeRegistry::default();
+    registry.write().register::<Tracked>();
+
+    let mut map = Tracked::new();
+    map.insert(7, 70, Tick::new(1));
+    map.insert(3, 30, Tick::new(2));
+    map.shift_remove(&7, Tick::new(3));
+
+    let mut source = World::new();
+    let entity = source.spawn(map).id();
+    let mut destination = World::new();
+    let copy = destination.spawn_empty().id();
+
+    let registry = registry.read();
+    let reflect_component = registry
+        .get_type_data::<ReflectComponent>(::core::any::TypeId::of::<Tracked>())
+        .unwrap();
+    reflect_component.copy(&source, &mut destination, entity, copy, &registry);
+
+    let copied = destination.get::<Tracked>(copy).unwrap();
+    assert!(copied.iter().eq([(&3, &30)]));
+    assert_eq!(copied.get_tick(&3), Some(Tick::new(2)));
+    assert!(copied.removed_since(Tick::new(2), Tick::new(10)).eq(&[7]));
+}
+
+#[test]
+#[cfg(feature = "bevy_ecs")]
+fn observed_index_map_events() {
+    use crate::ecs::observed::*;
+    use alloc::format;
+    use bevy_ecs::prelude::*;
+    use bevy_ecs::system::RunSystemOnce;
+    use std::hash::RandomState;
+
+    type Events = Vec<String>;
+    type Map = ObservedIndexMap<&'static str, i32>;
+
+    #[derive(Resource, Default)]
+    struct Log(Events);
+
+    let mut world = World::new();
+    world.init_resource::<Log>();
+    world.insert_resource(Map::default());
+    world.add_observer(
+        |t: Trigger<'_, EntryInserted<&str, i32>>, mut log: ResMut<'_, Log>| {
+            log.0.push(format!("+{}@{}", t.key, t.index));
+        },
+    );
+    world.add_observer(
+        |t: Trigger<'_, EntryRemoved<&str, i32>>, mut log: ResMut<'_, Log>| {
+            log.0.push(format!("-{}@{}", t.key, t.index));
+        },
+    );
+    world.add_observer(
+        |t: Trigger<'_, EntriesRemoved<&str, i32>>, mut log: ResMut<'_, Log>| {
+            let keys: Vec<String> = t.keys.iter().map(|(i, k)| format!("{k}@{i}")).collect();
+            log.0.push(format!("-[{}]", keys.join(", ")));
+        },
+    );
+    world.add_observer(
+        |t: Trigger<'_, EntryMoved<&str, i32>>, mut log: ResMut<'_, Log>| {
+            log.0.push(format!("{}:{}->{}", t.key, t.from, t.to));
+        },
+    );
+    world.add_observer(
+        |_: Trigger<'_, Reordered<&str, i32>>, mut log: ResMut<'_, Log>| {
+            log.0.push("reordered".into());
+        },
+    );
+    let flush = |world: &mut World| -> Events {
+        world
+            .run_system_once(trigger_index_map_events::<&str, i32, RandomState>)
+            .unwrap();
+        ::core::mem::take(&mut world.resource_mut::<Log>().0)
+    };
+
+    let mut map = world.resource_mut::<Map>();
+    for (i, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+        map.insert(key, i as i32);
+    }
+    assert_eq!(map.insert("a", 10), Some(0));
+    *map.entry("b").or_insert(0) += 10;
+    if let Entry::Vacant(entry) = map.entry("e") {
+        entry.shift_insert(1, 4);
+    }
+    assert_eq!(map.queued_events(), 5);
+    // Nothing is triggered before the flush.
+    assert!(world.resource::<Log>().0.is_empty());
+    assert_eq!(flush(&mut world), ["+a@0", "+b@1", "+c@2", "+d@3", "+e@1"]);
+
+    let mut map = world.resource_mut::<Map>();
+    assert_eq!(map.swap_remove("a"), Some(10));
+    map.move_index(0, 3);
+    if let Entry::Occupied(entry) = map.entry("b") {
+        entry.swap_indices(0);
+    }
+    assert!(map.keys().eq(["b", "e", "c", "d"].iter()));
+    assert_eq!(
+        flush(&mut world),
+        ["-a@0", "d:4->0", "d:0->3", "b:1->0", "e:0->1"]
+    );
+
+    let mut map = world.resource_mut::<Map>();
+    map.retain(|_, value| *value % 2 == 0);
+    map.sort_keys();
+    map.sort_by(|_, a, _, b| b.cmp(a));
+    map.drain(..1);
+    assert!(map.keys().eq(["c"].iter()));
+    assert_eq!(
+        flush(&mut world),
+        ["-[b@0, d@3]", "reordered", "reordered", "-[e@0]"]
+    );
+
+    // Changing values doesn't queue events.
+    let mut map = world.resource_mut::<Map>();
+    *map.get_mut("c").unwrap() += 1;
+    map.clear();
+    assert_eq!(flush(&mut world), ["-[c@0]"]);
+    // Clearing an empty map doesn't queue an event.
+    world.resource_mut::<Map>().clear();
+    assert_eq!(flush(&mut world), Events::new());
+}
+
+#[test]
+#[cfg(feature = "bevy_ecs")]
+fn ordered_registry() {
+    use crate::ecs::registry::{OrderedRegistry, OrderedRegistryMut, Registered};
+    use bevy_ecs::prelude::*;
+    use bevy_ecs::system::RunSystemOnce;
+
+    type Registry = OrderedRegistry<&'static str>;
+
+    fn entries(world: &World) -> Vec<(&'static str, Entity)> {
+        let registry = world.resource::<Registry>();
+        let entries: Vec<_> = registry.iter().map(|(&k, &e)| (k, e)).collect();
+        for (index, &(key, entity)) in entries.iter().enumerate() {
+            assert_eq!(registry.get(key), Some(entity));
+            assert_eq!(registry.get_key(entity), Some(&key));
+            assert_eq!(registry.get_index_of_entity(entity), Some(index));
+            assert!(world.get::<Registered<&str>>(entity).is_some());
+        }
+        assert!(registry.entities().eq(entries.iter().map(|(_, e)| e)));
+        entries
+    }
+
+    let mut world = World::new();
+    world.init_resource::<Registry>();
+    let [a, b, c, d] = ::core::array::from_fn(|_| world.spawn_empty().id());
+
+    world
+        .run_system_once(
+            move |mut registry: OrderedRegistryMut<'_, '_, &'static str>| {
+                assert_eq!(registry.register("a", a), None);
+                assert_eq!(registry.register("b", b), None);
+                assert_eq!(registry.register("c", c), None);
+                // The key keeps its index, and the old entity is unregistered.
+                assert_eq!(registry.register("b", d), Some(b));
+                // The entity moves to its new key.
+                assert_eq!(registry.register("e", a), None);
+                assert_eq!(registry.register("e", a), Some(a));
+            },
+        )
+        .unwrap();
+    assert_eq!(entries(&world), [("b", d), ("c", c), ("e", a)]);
+    assert!(world.get::<Registered<&str>>(b).is_none());
+
+    world.despawn(c);
+    assert_eq!(entries(&world), [("b", d), ("e", a)]);
+    world.entity_mut(d).remove::<Registered<&str>>();
+    assert_eq!(entries(&world), [("e", a)]);
+
+    let f = world.spawn_empty().id();
+    world
+        .run_system_once(
+            move |mut registry: OrderedRegistryMut<'_, '_, &'static str>| {
+                registry.register("f", f);
+                registry.register("b", b);
+                registry.register("d", d);
+                assert_eq!(registry.unregister("e"), Some(a));
+                // Registered again before the commands are applied.
+                registry.register("a", a);
+                assert_eq!(registry.unregister_entity(b), Some("b"));
+                registry.sort_keys();
+                registry.swap_indices(0, 2);
+            },
+        )
+        .unwrap();
+    assert_eq!(entries(&world), [("f", f), ("d", d), ("a", a)]);
+    assert!(world.get::<Registered<&str>>(b).is_none());
+
+    // Entities despawned before the commands are applied are forgotten.
+    world
+        .run_system_once(
+            move |mut registry: OrderedRegistryMut<'_, '_, &'static str>,
+                  mut commands: Commands<'_, '_>| {
+                commands.entity(b).despawn();
+                registry.register("b", b);
+                registry.move_index(0, 2);
+            },
+        )
+        .unwrap();
+    assert_eq!(entries(&world), [("d", d), ("a", a), ("f", f)]);
+}
+
+#[test]
+#[cfg(all(feature = "bevy_ecs", feature = "bevy_reflect"))]
+fn ordered_registry_reflect() {
+    use crate::ecs::OrderedRegistry;
+    use bevy_ecs::prelude::*;
+    use bevy_ecs::reflect::{AppTypeRegistry, ReflectResource};
+    use bevy_reflect::ReflectRef;
+
+    type Registry = OrderedRegistry<u32>;
+
+    let mut world = World::new();
+    let registry = AppTypeRegistry::default();
+    registry.write().register::<Registry>();
+    world.insert_resource(Registry::default());
+
+    let registry = registry.read();
+    let reflect_resource =
+        registry.get_type_data::<ReflectResource>(::core::any::TypeId::of::<Registry>());
+    let reflected = reflect_resource.unwrap().reflect(&world).unwrap();
+    match reflected.reflect_ref() {
+        ReflectRef::Struct(fields) => assert!(fields.field("by_key").is_some()),
+        _ => panic!("expected a struct"),
+    }
+}
@@ -1,3 +1,27 @@
+//! Integration of [`IndexMap`][crate::IndexMap] and [`IndexSet`][crate::IndexSet] with
+//! [`bevy_reflect`].
+
+mod apply;
+mod dynamic;
+#[cfg(feature = "functions")]
+mod functions;
+mod hasher;
+mod indexed;
+mod map;
+mod patch;
+#[cfg(feature = "serde")]
+mod serde;
+mod set;
+mod slice;
+#[cfg(test)]
+mod tests;
+
+pub use self::apply::{MapApplyMode, ReflectMapApply};
+pub use self::dynamic::{DynamicIndexMap, DynamicIndexSet};
+pub use self::hasher::BuildReflectHasher;
+pub use self::indexed::{ReflectIndexed, ReflectIndexedError};
+pub use self::patch::{DynamicIndexMapPatch, IndexMapPatch};
+
 macro_rules! impl_reflect_for_hashmap {
 
     ($ty:path) => {
@@ -10,7 +34,7 @@ macro_rules! impl_reflect_for_hashmap {
 
             V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
 
-            S: TypePath + BuildHasher + Default + Send + Sync,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
@@ -38,9 +62,7 @@ macro_rules! impl_reflect_for_hashmap {
 
             fn get_at(&self, index: usize) -> Option<(&dyn PartialReflect, &dyn PartialReflect)> {
 
-                self.iter()
-
-                    .nth(index)
+                self.get_index(index)
 
                     .map(|(key, value)| (key as &dyn PartialReflect, value as &dyn PartialReflect))
 
@@ -55,7 +77,7 @@ macro_rules! impl_reflect_for_hashmap {
 
             ) -> Option<(&dyn PartialReflect, &mut dyn PartialReflect)> {
 
-                self.iter_mut().nth(index).map(|(key, value)| {
+                self.get_index_mut(index).map(|(key, value)| {
 
                     (key as &dyn PartialReflect, value as &mut dyn PartialReflect)
 
@@ -80,7 +102,7 @@ macro_rules! impl_reflect_for_hashmap {
 
             fn drain(&mut self) -> Vec<(Box<dyn PartialReflect>, Box<dyn PartialReflect>)> {
 
-                self.drain(0..self.len()-1)
+                self.drain(..)
 
                     .map(|(key, value)| {
 
@@ -200,7 +222,7 @@ macro_rules! impl_reflect_for_hashmap {
 
             V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
 
-            S: TypePath + BuildHasher + Default + Send + Sync,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
@@ -289,7 +311,17 @@ macro_rules! impl_reflect_for_hashmap {
 
             fn reflect_clone(&self) -> Result<Box<dyn Reflect>, ReflectCloneError> {
 
-                let mut map = Self::with_capacity_and_hasher(self.len(), S::default());
+                let hasher = self.hasher().clone_reflect_hasher().ok_or_else(|| {
+
+                    ReflectCloneError::NotCloneable {
+
+                        type_path: Cow::Borrowed(<Self as TypePath>::type_path()),
+
+                    }
+
+                })?;
+
+                let mut map = Self::with_capacity_and_hasher(self.len(), hasher);
 
                 for (key, value) in self.iter() {
 
@@ -327,6 +359,13 @@ macro_rules! impl_reflect_for_hashmap {
             }
 
 
+            fn to_dynamic(&self) -> Box<dyn PartialReflect> {
+
+                Box::new(DynamicIndexMap::from_map(self))
+
+            }
+
+
             fn reflect_partial_eq(&self, value: &dyn PartialReflect) -> Option<bool> {
 
                 map_partial_eq(self, value)
@@ -360,7 +399,7 @@ macro_rules! impl_reflect_for_hashmap {
 
                 V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
 
-                S: TypePath + BuildHasher + Default + Send + Sync,
+                S: TypePath + BuildReflectHasher + Send + Sync,
 
         );
 
@@ -373,7 +412,7 @@ macro_rules! impl_reflect_for_hashmap {
 
             V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
 
-            S: TypePath + BuildHasher + Default + Send + Sync,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
@@ -410,7 +449,7 @@ macro_rules! impl_reflect_for_hashmap {
 
             V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
 
-            S: TypePath + BuildHasher + Default + Send + Sync + Default,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
@@ -422,6 +461,12 @@ macro_rules! impl_reflect_for_hashmap {
 
                 registration.insert::<ReflectFromReflect>(FromType::<Self>::from_type());
 
+                S::register_map_type_data::<K, V>(&mut registration);
+
+                registration.insert::<ReflectIndexed>(FromType::<Self>::from_type());
+
+                registration.insert::<ReflectMapApply>(FromType::<Self>::from_type());
+
                 registration
 
             }
@@ -446,30 +491,13 @@ macro_rules! impl_reflect_for_hashmap {
 
             V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
 
-            S: TypePath + BuildHasher + Default + Send + Sync,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
             fn from_reflect(reflect: &dyn PartialReflect) -> Option<Self> {
 
-                let ref_map = reflect.reflect_ref().as_map().ok()?;
-
-
-                let mut new_map = Self::with_capacity_and_hasher(ref_map.len(), S::default());
-
-
-                for (key, value) in ref_map.iter() {
-
-                    let new_key = K::from_reflect(key)?;
-
-                    let new_value = V::from_reflect(value)?;
-
-                    new_map.insert(new_key, new_value);
-
-                }
-
-
-                Some(new_map)
+                Self::from_reflect_with_hasher(reflect, S::build_reflect_hasher()?)
 
             }
 
@@ -557,7 +585,7 @@ macro_rules! impl_reflect_for_hashset {
 
             V: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
 
-            S: TypePath + BuildHasher + Default + Send + Sync,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
@@ -592,7 +620,7 @@ macro_rules! impl_reflect_for_hashset {
 
             fn drain(&mut self) -> Vec<Box<dyn PartialReflect>> {
 
-                self.drain(0..self.len()-1)
+                self.drain(..)
 
                     .map(|value| Box::new(value) as Box<dyn PartialReflect>)
 
@@ -620,6 +648,8 @@ macro_rules! impl_reflect_for_hashset {
             }
 
 
+            // bevy_reflect already needs a toolchain newer than the crate's MSRV.
+            #[allow(clippy::incompatible_msrv)]
             fn remove(&mut self, value: &dyn PartialReflect) -> bool {
 
                 let mut from_reflect = None;
@@ -641,6 +671,8 @@ macro_rules! impl_reflect_for_hashset {
             }
 
 
+            // bevy_reflect already needs a toolchain newer than the crate's MSRV.
+            #[allow(clippy::incompatible_msrv)]
             fn contains(&self, value: &dyn PartialReflect) -> bool {
 
                 let mut from_reflect = None;
@@ -670,7 +702,7 @@ macro_rules! impl_reflect_for_hashset {
 
             V: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
 
-            S: TypePath + BuildHasher + Default + Send + Sync,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
@@ -775,7 +807,17 @@ macro_rules! impl_reflect_for_hashset {
 
             fn reflect_clone(&self) -> Result<Box<dyn Reflect>, ReflectCloneError> {
 
-                let mut set = Self::with_capacity_and_hasher(self.len(), S::default());
+                let hasher = self.hasher().clone_reflect_hasher().ok_or_else(|| {
+
+                    ReflectCloneError::NotCloneable {
+
+                        type_path: Cow::Borrowed(<Self as TypePath>::type_path()),
+
+                    }
+
+                })?;
+
+                let mut set = Self::with_capacity_and_hasher(self.len(), hasher);
 
                 for value in self.iter() {
 
@@ -801,6 +843,13 @@ macro_rules! impl_reflect_for_hashset {
             }
 
 
+            fn to_dynamic(&self) -> Box<dyn PartialReflect> {
+
+                Box::new(DynamicIndexSet::from_set(self))
+
+            }
+
+
             fn reflect_partial_eq(&self, value: &dyn PartialReflect) -> Option<bool> {
 
                 set_partial_eq(self, value)
@@ -816,7 +865,7 @@ macro_rules! impl_reflect_for_hashset {
 
             V: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
 
-            S: TypePath + BuildHasher + Default + Send + Sync,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
@@ -849,7 +898,7 @@ macro_rules! impl_reflect_for_hashset {
 
             V: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
 
-            S: TypePath + BuildHasher + Default + Send + Sync + Default,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
@@ -861,6 +910,10 @@ macro_rules! impl_reflect_for_hashset {
 
                 registration.insert::<ReflectFromReflect>(FromType::<Self>::from_type());
 
+                S::register_set_type_data::<V>(&mut registration);
+
+                registration.insert::<ReflectIndexed>(FromType::<Self>::from_type());
+
                 registration
 
             }
@@ -883,7 +936,7 @@ macro_rules! impl_reflect_for_hashset {
 
                 V: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
 
-                S: TypePath + BuildHasher + Default + Send + Sync,
+                S: TypePath + BuildReflectHasher + Send + Sync,
 
         );
 
@@ -894,28 +947,13 @@ macro_rules! impl_reflect_for_hashset {
 
             V: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
 
-            S: TypePath + BuildHasher + Default + Send + Sync,
+            S: TypePath + BuildReflectHasher + Send + Sync,
 
         {
 
             fn from_reflect(reflect: &dyn PartialReflect) -> Option<Self> {
 
-                let ref_set = reflect.reflect_ref().as_set().ok()?;
-
-
-                let mut new_set = Self::with_capacity_and_hasher(ref_set.len(), S::default());
-
-
-                for value in ref_set.iter() {
-
-                    let new_value = V::from_reflect(value)?;
-
-                    new_set.insert(new_value);
-
-                }
-
-
-                Some(new_set)
+                Self::from_reflect_with_hasher(reflect, S::build_reflect_hasher()?)
 
             }
 
@@ -0,0 +1,184 @@
+use super::hasher::BuildReflectHasher;
+use super::indexed::index_of;
+use crate::IndexMap;
+
+use alloc::string::ToString;
+use core::hash::Hash;
+
+use bevy_reflect::{
+    map_try_apply, ApplyError, FromReflect, FromType, GetTypeRegistration, MaybeTyped,
+    PartialReflect, TypePath,
+};
+
+/// How a reflected [`IndexMap`] treats the order of its entries when a value is applied to it.
+///
+/// [`PartialReflect::apply`] and [`PartialReflect::try_apply`] always use
+/// [`Merge`][MapApplyMode::Merge], which matches every other `bevy_reflect` map. The other
+/// modes are used through [`ReflectMapApply`], or [`IndexMap::try_apply_with_mode`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MapApplyMode {
+    /// Update the values of existing keys in place, and append missing keys at the end
+    /// in the order of the source.
+    #[default]
+    Merge,
+    /// Like [`Merge`][Self::Merge], then reorder the entries so the keys of the source
+    /// come first, in the same order as the source.
+    ///
+    /// Keys that are absent from the source are kept after those, in their relative order.
+    MirrorOrder,
+    /// Like [`MirrorOrder`][Self::MirrorOrder], then remove all keys that are absent from
+    /// the source, so the map ends up with exactly the keys of the source, in its order.
+    Replace,
+}
+
+/// Type data for applying values to a reflected [`IndexMap`] with a [`MapApplyMode`].
+///
+/// This is registered for every reflected `IndexMap` with the [`Merge`][MapApplyMode::Merge]
+/// mode. The mode can be chosen per type by changing the registered data with
+/// [`set_mode`][Self::set_mode], and code that applies reflected values through the
+/// registry can use [`apply`][Self::apply] or [`try_apply`][Self::try_apply] to respect it.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_reflect::{DynamicMap, PartialReflect, TypeRegistry};
+/// use indexmap::reflect::{MapApplyMode, ReflectMapApply};
+/// use indexmap::IndexMap;
+///
+/// let mut registry = TypeRegistry::default();
+/// registry.register::<IndexMap<u32, u32>>();
+/// registry
+///     .get_type_data_mut::<ReflectMapApply>(core::any::TypeId::of::<IndexMap<u32, u32>>())
+///     .unwrap()
+///     .set_mode(MapApplyMode::Replace);
+///
+/// let mut map: IndexMap<u32, u32> = (0..4).map(|i| (i, i)).collect();
+/// let mut patch = DynamicMap::default();
+/// patch.insert(2u32, 20u32);
+/// patch.insert(0u32, 0u32);
+///
+/// let apply = registry
+///     .get_type_data::<ReflectMapApply>(core::any::TypeId::of::<IndexMap<u32, u32>>())
+///     .unwrap();
+/// apply.apply(&mut map, &patch);
+///
+/// assert!(map.iter().eq([(&2, &20), (&0, &0)]));
+/// ```
+#[derive(Clone)]
+pub struct ReflectMapApply {
+    mode: MapApplyMode,
+    try_apply:
+        fn(&mut dyn PartialReflect, &dyn PartialReflect, MapApplyMode) -> Result<(), ApplyError>,
+}
+
+impl ReflectMapApply {
+    /// Return the mode used by [`apply`][Self::apply] and [`try_apply`][Self::try_apply].
+    pub fn mode(&self) -> MapApplyMode {
+        self.mode
+    }
+
+    /// Set the mode used by [`apply`][Self::apply] and [`try_apply`][Self::try_apply].
+    pub fn set_mode(&mut self, mode: MapApplyMode) {
+        self.mode = mode;
+    }
+
+    /// Applies `value` to `target` with the registered mode.
+    ///
+    /// ***Panics*** in the same cases as [`PartialReflect::apply`], or if `target` is not
+    /// the type this data was registered for.
+    pub fn apply(&self, target: &mut dyn PartialReflect, value: &dyn PartialReflect) {
+        if let Err(err) = self.try_apply(target, value) {
+            panic!("{err}");
+        }
+    }
+
+    /// Tries to apply `value` to `target` with the registered mode.
+    ///
+    /// As with [`PartialReflect::try_apply`], `target` may be partially updated
+    /// when an error is returned.
+    pub fn try_apply(
+        &self,
+        target: &mut dyn PartialReflect,
+        value: &dyn PartialReflect,
+    ) -> Result<(), ApplyError> {
+        (self.try_apply)(target, value, self.mode)
+    }
+}
+
+impl<K, V, S> FromType<IndexMap<K, V, S>> for ReflectMapApply
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    fn from_type() -> Self {
+        ReflectMapApply {
+            mode: MapApplyMode::Merge,
+            try_apply: try_apply_reflected::<K, V, S>,
+        }
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    /// Tries to apply a reflected map to this map with the given [`MapApplyMode`].
+    ///
+    /// With [`MapApplyMode::Merge`], this is the same as [`PartialReflect::try_apply`].
+    /// As with that method, the map may be partially updated when an error is returned.
+    ///
+    /// Computes in **O(n²)** time in the worst case for the reordering modes,
+    /// since each key of the source is moved into place with [`move_index`][Self::move_index].
+    pub fn try_apply_with_mode(
+        &mut self,
+        value: &dyn PartialReflect,
+        mode: MapApplyMode,
+    ) -> Result<(), ApplyError> {
+        map_try_apply(self, value)?;
+        if mode == MapApplyMode::Merge {
+            return Ok(());
+        }
+
+        // The source was already checked to be a map by `map_try_apply`.
+        let source = value.reflect_ref().as_map()?;
+        let mut next = 0;
+        for (key, _) in source.iter() {
+            if let Some(from) = index_of::<K, _>(key, |key| self.get_index_of(key)) {
+                // Distinct source keys may still convert to the same `K`,
+                // so only the first of those claims a position.
+                if from >= next {
+                    self.move_index(from, next);
+                    next += 1;
+                }
+            }
+        }
+
+        if mode == MapApplyMode::Replace {
+            self.truncate(next);
+        }
+        Ok(())
+    }
+}
+
+fn try_apply_reflected<K, V, S>(
+    target: &mut dyn PartialReflect,
+    value: &dyn PartialReflect,
+    mode: MapApplyMode,
+) -> Result<(), ApplyError>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let from_type = target.reflect_type_path().to_string();
+    let map = target
+        .try_downcast_mut::<IndexMap<K, V, S>>()
+        .ok_or_else(|| ApplyError::MismatchedTypes {
+            from_type: from_type.into_boxed_str(),
+            to_type: IndexMap::<K, V, S>::type_path().into(),
+        })?;
+    map.try_apply_with_mode(value, mode)
+}
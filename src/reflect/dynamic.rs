@@ -0,0 +1,671 @@
+use crate::{Equivalent, IndexMap, IndexSet};
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasherDefault, Hash, Hasher};
+
+use bevy_reflect::{
+    map_apply, map_debug, map_partial_eq, map_try_apply, set_apply, set_debug, set_partial_eq,
+    set_try_apply, ApplyError, DynamicMap, DynamicSet, Map, MapIter, PartialReflect, Reflect,
+    ReflectKind, ReflectMut, ReflectOwned, ReflectRef, Set, TypeInfo, TypePath,
+};
+
+/// A passthrough hasher for the precomputed [`PartialReflect::reflect_hash`] values.
+#[derive(Default)]
+struct ReflectHasher(u64);
+
+impl Hasher for ReflectHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(byte);
+        }
+    }
+
+    fn write_u64(&mut self, hash: u64) {
+        self.0 = hash;
+    }
+}
+
+type ReflectState = BuildHasherDefault<ReflectHasher>;
+
+fn reflect_hash(value: &dyn PartialReflect) -> u64 {
+    value.reflect_hash().unwrap_or_else(|| {
+        panic!(
+            "the given key of type `{}` does not support hashing",
+            value.reflect_type_path()
+        )
+    })
+}
+
+fn reflect_eq(a: &dyn PartialReflect, b: &dyn PartialReflect) -> bool {
+    a.reflect_partial_eq(b).expect(
+        "underlying type does not reflect `PartialEq` and hence doesn't support equality checks",
+    )
+}
+
+/// An owned key, hashed and compared through reflection.
+struct DynamicKey(Box<dyn PartialReflect>);
+
+impl Hash for DynamicKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(reflect_hash(&*self.0));
+    }
+}
+
+impl PartialEq for DynamicKey {
+    fn eq(&self, other: &Self) -> bool {
+        reflect_eq(&*self.0, &*other.0)
+    }
+}
+
+impl Eq for DynamicKey {}
+
+/// A borrowed key for lookups, hashed and compared through reflection.
+struct DynamicKeyRef<'a>(&'a dyn PartialReflect);
+
+impl Hash for DynamicKeyRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(reflect_hash(self.0));
+    }
+}
+
+impl Equivalent<DynamicKey> for DynamicKeyRef<'_> {
+    fn equivalent(&self, key: &DynamicKey) -> bool {
+        reflect_eq(self.0, &*key.0)
+    }
+}
+
+/// Convert a key to an owned value, preferring its concrete type so it can be hashed.
+pub(super) fn clone_key(key: &dyn PartialReflect) -> Box<dyn PartialReflect> {
+    match key.reflect_clone() {
+        Ok(key) => key.into_partial_reflect(),
+        Err(_) => key.to_dynamic(),
+    }
+}
+
+/// A dynamic map of reflected key-value pairs that preserves insertion order.
+///
+/// This is the ordered counterpart to [`DynamicMap`], which removes entries by swapping
+/// them with the last one. Like [`IndexMap`], the entries of a `DynamicIndexMap` keep the
+/// order in which they were inserted, removals shift the following entries, and entries
+/// can be accessed and moved by index.
+///
+/// This is the dynamic representation returned by [`PartialReflect::to_dynamic`] for a
+/// reflected [`IndexMap`], so the order of its entries is carried through
+/// [`to_dynamic`][PartialReflect::to_dynamic], [`apply`][PartialReflect::apply], and
+/// [`FromReflect`][bevy_reflect::FromReflect].
+#[derive(Default, TypePath)]
+pub struct DynamicIndexMap {
+    represented_type: Option<&'static TypeInfo>,
+    map: IndexMap<DynamicKey, Box<dyn PartialReflect>, ReflectState>,
+}
+
+impl DynamicIndexMap {
+    /// Create a `DynamicIndexMap` with the entries of `map`, in its iteration order.
+    ///
+    /// The represented type is also copied from `map`.
+    pub fn from_map(map: &dyn Map) -> Self {
+        let mut dynamic_map = DynamicIndexMap {
+            represented_type: None,
+            map: IndexMap::with_capacity_and_hasher(map.len(), ReflectState::default()),
+        };
+        dynamic_map.set_represented_type(map.get_represented_type_info());
+        for (key, value) in map.iter() {
+            dynamic_map.insert_boxed(clone_key(key), value.to_dynamic());
+        }
+        dynamic_map
+    }
+
+    /// Sets the [type] to be represented by this `DynamicIndexMap`.
+    ///
+    /// ***Panics*** if the given [type] is not a [`TypeInfo::Map`].
+    ///
+    /// [type]: TypeInfo
+    pub fn set_represented_type(&mut self, represented_type: Option<&'static TypeInfo>) {
+        if let Some(represented_type) = represented_type {
+            assert!(
+                matches!(represented_type, TypeInfo::Map(_)),
+                "expected TypeInfo::Map but received: {:?}",
+                represented_type
+            );
+        }
+
+        self.represented_type = represented_type;
+    }
+
+    /// Inserts a typed key-value pair into the map.
+    ///
+    /// If an equal key already exists, its value is replaced in the same position,
+    /// otherwise the new pair is inserted last in order.
+    pub fn insert<K: PartialReflect, V: PartialReflect>(&mut self, key: K, value: V) {
+        self.insert_boxed(Box::new(key), Box::new(value));
+    }
+
+    /// Return the number of key-value pairs in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the index of the entry with a key equal to `key`, if it exists.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of(&self, key: &dyn PartialReflect) -> Option<usize> {
+        self.map.get_index_of(&DynamicKeyRef(key))
+    }
+
+    /// Get a key-value pair by index.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<(&dyn PartialReflect, &dyn PartialReflect)> {
+        self.map
+            .get_index(index)
+            .map(|(key, value)| (&*key.0, &**value))
+    }
+
+    /// Get a key-value pair by index, with mutable access to the value.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index_mut(
+        &mut self,
+        index: usize,
+    ) -> Option<(&dyn PartialReflect, &mut dyn PartialReflect)> {
+        self.map
+            .get_index_mut(index)
+            .map(|(key, value)| (&*key.0, &mut **value))
+    }
+
+    /// Insert a key-value pair at the given index, or move an existing key there
+    /// and replace its value, returning the old value.
+    ///
+    /// ***Panics*** if `index` is out of bounds, as in [`IndexMap::shift_insert`].
+    ///
+    /// Computes in **O(n)** time (average).
+    #[track_caller]
+    pub fn shift_insert(
+        &mut self,
+        index: usize,
+        key: Box<dyn PartialReflect>,
+        value: Box<dyn PartialReflect>,
+    ) -> Option<Box<dyn PartialReflect>> {
+        self.map.shift_insert(index, DynamicKey(key), value)
+    }
+
+    /// Remove the key-value pair equal to `key` by shifting all of the
+    /// entries that follow it, and return its value.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_remove(&mut self, key: &dyn PartialReflect) -> Option<Box<dyn PartialReflect>> {
+        self.map.shift_remove(&DynamicKeyRef(key))
+    }
+
+    /// Remove the key-value pair at `index` by shifting all of the entries
+    /// that follow it.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_remove_index(
+        &mut self,
+        index: usize,
+    ) -> Option<(Box<dyn PartialReflect>, Box<dyn PartialReflect>)> {
+        self.map
+            .shift_remove_index(index)
+            .map(|(key, value)| (key.0, value))
+    }
+
+    /// Moves the position of a key-value pair from one index to another
+    /// by shifting all other pairs in-between.
+    ///
+    /// ***Panics*** if `from` or `to` are out of bounds.
+    ///
+    /// Computes in **O(n)** time (average).
+    #[track_caller]
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.map.move_index(from, to);
+    }
+
+    /// Swaps the position of two key-value pairs in the map.
+    ///
+    /// ***Panics*** if `a` or `b` are out of bounds.
+    ///
+    /// Computes in **O(1)** time (average).
+    #[track_caller]
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.map.swap_indices(a, b);
+    }
+}
+
+impl Map for DynamicIndexMap {
+    fn get(&self, key: &dyn PartialReflect) -> Option<&dyn PartialReflect> {
+        self.map.get(&DynamicKeyRef(key)).map(|value| &**value)
+    }
+
+    fn get_mut(&mut self, key: &dyn PartialReflect) -> Option<&mut dyn PartialReflect> {
+        self.map
+            .get_mut(&DynamicKeyRef(key))
+            .map(|value| &mut **value)
+    }
+
+    fn get_at(&self, index: usize) -> Option<(&dyn PartialReflect, &dyn PartialReflect)> {
+        self.get_index(index)
+    }
+
+    fn get_at_mut(
+        &mut self,
+        index: usize,
+    ) -> Option<(&dyn PartialReflect, &mut dyn PartialReflect)> {
+        self.get_index_mut(index)
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn iter(&self) -> MapIter<'_> {
+        MapIter::new(self)
+    }
+
+    fn drain(&mut self) -> Vec<(Box<dyn PartialReflect>, Box<dyn PartialReflect>)> {
+        self.map
+            .drain(..)
+            .map(|(key, value)| (key.0, value))
+            .collect()
+    }
+
+    fn to_dynamic_map(&self) -> DynamicMap {
+        let mut dynamic_map = DynamicMap::default();
+        dynamic_map.set_represented_type(self.represented_type);
+        for (key, value) in self.map.iter() {
+            dynamic_map.insert_boxed(clone_key(&*key.0), value.to_dynamic());
+        }
+        dynamic_map
+    }
+
+    fn insert_boxed(
+        &mut self,
+        key: Box<dyn PartialReflect>,
+        value: Box<dyn PartialReflect>,
+    ) -> Option<Box<dyn PartialReflect>> {
+        assert_eq!(
+            key.reflect_partial_eq(&*key),
+            Some(true),
+            "keys inserted in `Map`-like types are expected to reflect `PartialEq`"
+        );
+        self.map.insert(DynamicKey(key), value)
+    }
+
+    fn remove(&mut self, key: &dyn PartialReflect) -> Option<Box<dyn PartialReflect>> {
+        self.shift_remove(key)
+    }
+}
+
+impl PartialReflect for DynamicIndexMap {
+    #[inline]
+    fn get_represented_type_info(&self) -> Option<&'static TypeInfo> {
+        self.represented_type
+    }
+
+    #[inline]
+    fn into_partial_reflect(self: Box<Self>) -> Box<dyn PartialReflect> {
+        self
+    }
+
+    #[inline]
+    fn as_partial_reflect(&self) -> &dyn PartialReflect {
+        self
+    }
+
+    #[inline]
+    fn as_partial_reflect_mut(&mut self) -> &mut dyn PartialReflect {
+        self
+    }
+
+    fn try_into_reflect(self: Box<Self>) -> Result<Box<dyn Reflect>, Box<dyn PartialReflect>> {
+        Err(self)
+    }
+
+    fn try_as_reflect(&self) -> Option<&dyn Reflect> {
+        None
+    }
+
+    fn try_as_reflect_mut(&mut self) -> Option<&mut dyn Reflect> {
+        None
+    }
+
+    fn apply(&mut self, value: &dyn PartialReflect) {
+        map_apply(self, value);
+    }
+
+    fn try_apply(&mut self, value: &dyn PartialReflect) -> Result<(), ApplyError> {
+        map_try_apply(self, value)
+    }
+
+    fn reflect_kind(&self) -> ReflectKind {
+        ReflectKind::Map
+    }
+
+    fn reflect_ref(&self) -> ReflectRef<'_> {
+        ReflectRef::Map(self)
+    }
+
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
+        ReflectMut::Map(self)
+    }
+
+    fn reflect_owned(self: Box<Self>) -> ReflectOwned {
+        ReflectOwned::Map(self)
+    }
+
+    fn to_dynamic(&self) -> Box<dyn PartialReflect> {
+        Box::new(DynamicIndexMap::from_map(self))
+    }
+
+    fn reflect_partial_eq(&self, value: &dyn PartialReflect) -> Option<bool> {
+        map_partial_eq(self, value)
+    }
+
+    fn debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynamicIndexMap(")?;
+        map_debug(self, f)?;
+        write!(f, ")")
+    }
+
+    #[inline]
+    fn is_dynamic(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for DynamicIndexMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.debug(f)
+    }
+}
+
+impl FromIterator<(Box<dyn PartialReflect>, Box<dyn PartialReflect>)> for DynamicIndexMap {
+    fn from_iter<I: IntoIterator<Item = (Box<dyn PartialReflect>, Box<dyn PartialReflect>)>>(
+        items: I,
+    ) -> Self {
+        let mut map = Self::default();
+        for (key, value) in items {
+            map.insert_boxed(key, value);
+        }
+        map
+    }
+}
+
+/// A dynamic set of reflected values that preserves insertion order.
+///
+/// This is the ordered counterpart to [`DynamicSet`], which does not keep any order at all.
+/// Like [`IndexSet`], the values of a `DynamicIndexSet` keep the order in which they were
+/// inserted, removals shift the following values, and values can be accessed and moved
+/// by index.
+///
+/// This is the dynamic representation returned by [`PartialReflect::to_dynamic`] for a
+/// reflected [`IndexSet`], so the order of its values is carried through
+/// [`to_dynamic`][PartialReflect::to_dynamic], [`apply`][PartialReflect::apply], and
+/// [`FromReflect`][bevy_reflect::FromReflect].
+#[derive(Default, TypePath)]
+pub struct DynamicIndexSet {
+    represented_type: Option<&'static TypeInfo>,
+    set: IndexSet<DynamicKey, ReflectState>,
+}
+
+impl DynamicIndexSet {
+    /// Create a `DynamicIndexSet` with the values of `set`, in its iteration order.
+    ///
+    /// The represented type is also copied from `set`.
+    pub fn from_set(set: &dyn Set) -> Self {
+        let mut dynamic_set = DynamicIndexSet {
+            represented_type: None,
+            set: IndexSet::with_capacity_and_hasher(set.len(), ReflectState::default()),
+        };
+        dynamic_set.set_represented_type(set.get_represented_type_info());
+        for value in set.iter() {
+            dynamic_set.insert_boxed(clone_key(value));
+        }
+        dynamic_set
+    }
+
+    /// Sets the [type] to be represented by this `DynamicIndexSet`.
+    ///
+    /// ***Panics*** if the given [type] is not a [`TypeInfo::Set`].
+    ///
+    /// [type]: TypeInfo
+    pub fn set_represented_type(&mut self, represented_type: Option<&'static TypeInfo>) {
+        if let Some(represented_type) = represented_type {
+            assert!(
+                matches!(represented_type, TypeInfo::Set(_)),
+                "expected TypeInfo::Set but received: {:?}",
+                represented_type
+            );
+        }
+
+        self.represented_type = represented_type;
+    }
+
+    /// Inserts a typed value into the set.
+    ///
+    /// If an equal value already exists, the set is not changed.
+    pub fn insert<V: PartialReflect>(&mut self, value: V) {
+        self.insert_boxed(Box::new(value));
+    }
+
+    /// Return the number of values in the set.
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Returns true if the set contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Return the index of a value equal to `value`, if it exists.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of(&self, value: &dyn PartialReflect) -> Option<usize> {
+        self.set.get_index_of(&DynamicKeyRef(value))
+    }
+
+    /// Get a value by index.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index(&self, index: usize) -> Option<&dyn PartialReflect> {
+        self.set.get_index(index).map(|value| &*value.0)
+    }
+
+    /// Insert the value at the given index, or move an existing equal value there.
+    ///
+    /// Returns `true` if the value was newly inserted.
+    ///
+    /// ***Panics*** if `index` is out of bounds, as in [`IndexSet::shift_insert`].
+    ///
+    /// Computes in **O(n)** time (average).
+    #[track_caller]
+    pub fn shift_insert(&mut self, index: usize, value: Box<dyn PartialReflect>) -> bool {
+        self.set.shift_insert(index, DynamicKey(value))
+    }
+
+    /// Remove the value equal to `value` by shifting all of the values that follow it.
+    ///
+    /// Returns `true` if the value was present.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_remove(&mut self, value: &dyn PartialReflect) -> bool {
+        self.set.shift_remove(&DynamicKeyRef(value))
+    }
+
+    /// Remove the value at `index` by shifting all of the values that follow it.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_remove_index(&mut self, index: usize) -> Option<Box<dyn PartialReflect>> {
+        self.set.shift_remove_index(index).map(|value| value.0)
+    }
+
+    /// Moves the position of a value from one index to another
+    /// by shifting all other values in-between.
+    ///
+    /// ***Panics*** if `from` or `to` are out of bounds.
+    ///
+    /// Computes in **O(n)** time (average).
+    #[track_caller]
+    pub fn move_index(&mut self, from: usize, to: usize) {
+        self.set.move_index(from, to);
+    }
+
+    /// Swaps the position of two values in the set.
+    ///
+    /// ***Panics*** if `a` or `b` are out of bounds.
+    ///
+    /// Computes in **O(1)** time (average).
+    #[track_caller]
+    pub fn swap_indices(&mut self, a: usize, b: usize) {
+        self.set.swap_indices(a, b);
+    }
+}
+
+impl Set for DynamicIndexSet {
+    fn get(&self, value: &dyn PartialReflect) -> Option<&dyn PartialReflect> {
+        self.set.get(&DynamicKeyRef(value)).map(|value| &*value.0)
+    }
+
+    fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn PartialReflect> + '_> {
+        Box::new(self.set.iter().map(|value| &*value.0))
+    }
+
+    fn drain(&mut self) -> Vec<Box<dyn PartialReflect>> {
+        self.set.drain(..).map(|value| value.0).collect()
+    }
+
+    fn to_dynamic_set(&self) -> DynamicSet {
+        let mut dynamic_set = DynamicSet::default();
+        dynamic_set.set_represented_type(self.represented_type);
+        for value in self.set.iter() {
+            dynamic_set.insert_boxed(clone_key(&*value.0));
+        }
+        dynamic_set
+    }
+
+    fn insert_boxed(&mut self, value: Box<dyn PartialReflect>) -> bool {
+        assert_eq!(
+            value.reflect_partial_eq(&*value),
+            Some(true),
+            "values inserted in `Set`-like types are expected to reflect `PartialEq`"
+        );
+        self.set.insert(DynamicKey(value))
+    }
+
+    fn remove(&mut self, value: &dyn PartialReflect) -> bool {
+        self.shift_remove(value)
+    }
+
+    fn contains(&self, value: &dyn PartialReflect) -> bool {
+        self.set.contains(&DynamicKeyRef(value))
+    }
+}
+
+impl PartialReflect for DynamicIndexSet {
+    #[inline]
+    fn get_represented_type_info(&self) -> Option<&'static TypeInfo> {
+        self.represented_type
+    }
+
+    #[inline]
+    fn into_partial_reflect(self: Box<Self>) -> Box<dyn PartialReflect> {
+        self
+    }
+
+    #[inline]
+    fn as_partial_reflect(&self) -> &dyn PartialReflect {
+        self
+    }
+
+    #[inline]
+    fn as_partial_reflect_mut(&mut self) -> &mut dyn PartialReflect {
+        self
+    }
+
+    fn try_into_reflect(self: Box<Self>) -> Result<Box<dyn Reflect>, Box<dyn PartialReflect>> {
+        Err(self)
+    }
+
+    fn try_as_reflect(&self) -> Option<&dyn Reflect> {
+        None
+    }
+
+    fn try_as_reflect_mut(&mut self) -> Option<&mut dyn Reflect> {
+        None
+    }
+
+    fn apply(&mut self, value: &dyn PartialReflect) {
+        set_apply(self, value);
+    }
+
+    fn try_apply(&mut self, value: &dyn PartialReflect) -> Result<(), ApplyError> {
+        set_try_apply(self, value)
+    }
+
+    fn reflect_kind(&self) -> ReflectKind {
+        ReflectKind::Set
+    }
+
+    fn reflect_ref(&self) -> ReflectRef<'_> {
+        ReflectRef::Set(self)
+    }
+
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
+        ReflectMut::Set(self)
+    }
+
+    fn reflect_owned(self: Box<Self>) -> ReflectOwned {
+        ReflectOwned::Set(self)
+    }
+
+    fn to_dynamic(&self) -> Box<dyn PartialReflect> {
+        Box::new(DynamicIndexSet::from_set(self))
+    }
+
+    fn reflect_partial_eq(&self, value: &dyn PartialReflect) -> Option<bool> {
+        set_partial_eq(self, value)
+    }
+
+    fn debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynamicIndexSet(")?;
+        set_debug(self, f)?;
+        write!(f, ")")
+    }
+
+    #[inline]
+    fn is_dynamic(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for DynamicIndexSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.debug(f)
+    }
+}
+
+impl FromIterator<Box<dyn PartialReflect>> for DynamicIndexSet {
+    fn from_iter<I: IntoIterator<Item = Box<dyn PartialReflect>>>(values: I) -> Self {
+        let mut set = Self::default();
+        for value in values {
+            set.insert_boxed(value);
+        }
+        set
+    }
+}
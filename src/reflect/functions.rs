@@ -0,0 +1,309 @@
+#![cfg_attr(docsrs, doc(cfg(feature = "functions")))]
+
+use super::BuildReflectHasher;
+use crate::{IndexMap, IndexSet};
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::hash::Hash;
+
+use bevy_reflect::func::args::{FromArg, GetOwnership};
+use bevy_reflect::func::{DynamicFunction, FunctionRegistrationError, FunctionRegistry};
+use bevy_reflect::{FromReflect, GetTypeRegistration, MaybeTyped, TypePath};
+
+impl<K, V, S> IndexMap<K, V, S>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash + Clone,
+    K: GetOwnership + for<'a> FromArg<This<'a> = K>,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Clone,
+    V: GetOwnership + for<'a> FromArg<This<'a> = V>,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    /// Register the methods of this map type in `registry`, as
+    /// [`DynamicFunction`]s, so that they can be called by name.
+    ///
+    /// Each function is named after the [type path] of the map and the method, like
+    /// `"indexmap::map::IndexMap<u32, i32, std::hash::random::RandomState>::insert"`,
+    /// and takes the map as its first argument, by reference or by mutable reference
+    /// like the method. The other arguments are taken by value, including the keys that
+    /// the method takes by reference. Keys and values that the method would return by
+    /// reference are cloned, and iterators are collected in a `Vec`. The registered
+    /// methods are:
+    ///
+    /// - `len`, `is_empty`, `contains_key`, `get`, `get_index`, `get_index_of`,
+    ///   `first`, `last`, `keys` and `values`, to read the map.
+    /// - `insert`, `insert_full`, `shift_insert`, `swap_remove`, `shift_remove`,
+    ///   `swap_remove_index`, `shift_remove_index`, `truncate` and `clear`, to add and
+    ///   remove entries.
+    /// - `move_index`, `swap_indices` and `reverse`, to reorder the map.
+    ///
+    /// The functions don't panic on out-of-bounds indices. Like `swap_remove_index` and
+    /// `shift_remove_index`, the `shift_insert`, `move_index` and `swap_indices`
+    /// functions return `None` for them, and wrap the result of the method in `Some`
+    /// otherwise. The sorting methods are registered separately by
+    /// [`register_sort_functions`][Self::register_sort_functions].
+    ///
+    /// Returns an error, without registering any function, if a function with the same
+    /// name as one of them is already registered.
+    ///
+    /// [`DynamicFunction`]: bevy_reflect::func::DynamicFunction
+    /// [type path]: TypePath::type_path
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bevy_reflect::func::{ArgList, FunctionRegistry};
+    /// use bevy_reflect::TypePath;
+    /// use indexmap::IndexMap;
+    ///
+    /// type Map = IndexMap<u32, i32>;
+    ///
+    /// let mut registry = FunctionRegistry::default();
+    /// Map::register_functions(&mut registry).unwrap();
+    ///
+    /// let insert = registry.get(&format!("{}::insert", Map::type_path())).unwrap();
+    /// let mut map = Map::new();
+    /// insert.call(ArgList::new().with_mut(&mut map).with_owned(1_u32).with_owned(-1)).unwrap();
+    /// insert.call(ArgList::new().with_mut(&mut map).with_owned(2_u32).with_owned(-2)).unwrap();
+    ///
+    /// let get_index = registry.get(&format!("{}::get_index", Map::type_path())).unwrap();
+    /// let entry = get_index.call(ArgList::new().with_ref(&map).with_owned(1_usize)).unwrap();
+    /// assert_eq!(entry.unwrap_owned().try_take::<Option<(u32, i32)>>().unwrap(), Some((2, -2)));
+    /// ```
+    pub fn register_functions(
+        registry: &mut FunctionRegistry,
+    ) -> Result<(), FunctionRegistrationError> {
+        let name = |method: &str| format!("{}::{}", Self::type_path(), method);
+        let mut functions = FunctionRegistry::default();
+        functions
+            .register_with_name(name("len"), |map: &Self| map.len())?
+            .register_with_name(name("is_empty"), |map: &Self| map.is_empty())?
+            .register_with_name(name("contains_key"), |map: &Self, key: K| {
+                map.contains_key(&key)
+            })?
+            .register_with_name(name("get"), |map: &Self, key: K| map.get(&key).cloned())?
+            .register_with_name(name("get_index"), |map: &Self, index: usize| {
+                cloned_entry(map.get_index(index))
+            })?
+            .register_with_name(name("get_index_of"), |map: &Self, key: K| {
+                map.get_index_of(&key)
+            })?
+            .register_with_name(name("first"), |map: &Self| cloned_entry(map.first()))?
+            .register_with_name(name("last"), |map: &Self| cloned_entry(map.last()))?
+            .register_with_name(name("keys"), |map: &Self| {
+                map.keys().cloned().collect::<Vec<K>>()
+            })?
+            .register_with_name(name("values"), |map: &Self| {
+                map.values().cloned().collect::<Vec<V>>()
+            })?
+            .register_with_name(name("insert"), |map: &mut Self, key: K, value: V| {
+                map.insert(key, value)
+            })?
+            .register_with_name(name("insert_full"), |map: &mut Self, key: K, value: V| {
+                map.insert_full(key, value)
+            })?
+            .register_with_name(
+                name("shift_insert"),
+                |map: &mut Self, index: usize, key: K, value: V| {
+                    // Existing keys can only move within `0..len`, new ones may also go at `len`.
+                    let max = map.len() + usize::from(!map.contains_key(&key));
+                    (index < max).then(|| map.shift_insert(index, key, value))
+                },
+            )?
+            .register_with_name(name("swap_remove"), |map: &mut Self, key: K| {
+                map.swap_remove(&key)
+            })?
+            .register_with_name(name("shift_remove"), |map: &mut Self, key: K| {
+                map.shift_remove(&key)
+            })?
+            .register_with_name(name("swap_remove_index"), |map: &mut Self, index: usize| {
+                map.swap_remove_index(index)
+            })?
+            .register_with_name(
+                name("shift_remove_index"),
+                |map: &mut Self, index: usize| map.shift_remove_index(index),
+            )?
+            .register_with_name(name("truncate"), |map: &mut Self, len: usize| {
+                map.truncate(len)
+            })?
+            .register_with_name(name("clear"), |map: &mut Self| map.clear())?
+            .register_with_name(
+                name("move_index"),
+                |map: &mut Self, from: usize, to: usize| {
+                    (from < map.len() && to < map.len()).then(|| map.move_index(from, to))
+                },
+            )?
+            .register_with_name(
+                name("swap_indices"),
+                |map: &mut Self, a: usize, b: usize| {
+                    (a < map.len() && b < map.len()).then(|| map.swap_indices(a, b))
+                },
+            )?
+            .register_with_name(name("reverse"), |map: &mut Self| map.reverse())?;
+        merge_functions(registry, functions)
+    }
+
+    /// Register the sorting methods of this map type in `registry`, as
+    /// [`DynamicFunction`]s named like those of
+    /// [`register_functions`][Self::register_functions].
+    ///
+    /// The registered methods are `sort_keys` and `sort_unstable_keys`. Like
+    /// [`register_functions`][Self::register_functions], nothing is registered if one
+    /// of their names is already taken.
+    ///
+    /// [`DynamicFunction`]: bevy_reflect::func::DynamicFunction
+    pub fn register_sort_functions(
+        registry: &mut FunctionRegistry,
+    ) -> Result<(), FunctionRegistrationError>
+    where
+        K: Ord,
+    {
+        let name = |method: &str| format!("{}::{}", Self::type_path(), method);
+        let mut functions = FunctionRegistry::default();
+        functions
+            .register_with_name(name("sort_keys"), |map: &mut Self| map.sort_keys())?
+            .register_with_name(name("sort_unstable_keys"), |map: &mut Self| {
+                map.sort_unstable_keys()
+            })?;
+        merge_functions(registry, functions)
+    }
+}
+
+impl<T, S> IndexSet<T, S>
+where
+    T: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash + Clone,
+    T: GetOwnership + for<'a> FromArg<This<'a> = T>,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    /// Register the methods of this set type in `registry`, as
+    /// [`DynamicFunction`]s, so that they can be called by name.
+    ///
+    /// The functions are named and called like those of
+    /// [`IndexMap::register_functions`]. The registered methods are:
+    ///
+    /// - `len`, `is_empty`, `contains`, `get_index`, `get_index_of`, `first`, `last`
+    ///   and `to_vec`, to read the set.
+    /// - `insert`, `insert_full`, `shift_insert`, `swap_remove`, `shift_remove`,
+    ///   `swap_remove_index`, `shift_remove_index`, `truncate` and `clear`, to add and
+    ///   remove values.
+    /// - `move_index`, `swap_indices` and `reverse`, to reorder the set.
+    ///
+    /// `to_vec` returns the values of the set in a `Vec`, in order. The functions that
+    /// take indices return `None` for out-of-bounds indices, like those of the map. The
+    /// sorting methods are registered separately by
+    /// [`register_sort_functions`][Self::register_sort_functions].
+    ///
+    /// [`DynamicFunction`]: bevy_reflect::func::DynamicFunction
+    pub fn register_functions(
+        registry: &mut FunctionRegistry,
+    ) -> Result<(), FunctionRegistrationError> {
+        let name = |method: &str| format!("{}::{}", Self::type_path(), method);
+        let mut functions = FunctionRegistry::default();
+        functions
+            .register_with_name(name("len"), |set: &Self| set.len())?
+            .register_with_name(name("is_empty"), |set: &Self| set.is_empty())?
+            .register_with_name(name("contains"), |set: &Self, value: T| {
+                set.contains(&value)
+            })?
+            .register_with_name(name("get_index"), |set: &Self, index: usize| {
+                set.get_index(index).cloned()
+            })?
+            .register_with_name(name("get_index_of"), |set: &Self, value: T| {
+                set.get_index_of(&value)
+            })?
+            .register_with_name(name("first"), |set: &Self| set.first().cloned())?
+            .register_with_name(name("last"), |set: &Self| set.last().cloned())?
+            .register_with_name(name("to_vec"), |set: &Self| {
+                set.iter().cloned().collect::<Vec<T>>()
+            })?
+            .register_with_name(name("insert"), |set: &mut Self, value: T| set.insert(value))?
+            .register_with_name(name("insert_full"), |set: &mut Self, value: T| {
+                set.insert_full(value)
+            })?
+            .register_with_name(
+                name("shift_insert"),
+                |set: &mut Self, index: usize, value: T| {
+                    // Existing values can only move within `0..len`, new ones may also go at `len`.
+                    let max = set.len() + usize::from(!set.contains(&value));
+                    (index < max).then(|| set.shift_insert(index, value))
+                },
+            )?
+            .register_with_name(name("swap_remove"), |set: &mut Self, value: T| {
+                set.swap_remove(&value)
+            })?
+            .register_with_name(name("shift_remove"), |set: &mut Self, value: T| {
+                set.shift_remove(&value)
+            })?
+            .register_with_name(name("swap_remove_index"), |set: &mut Self, index: usize| {
+                set.swap_remove_index(index)
+            })?
+            .register_with_name(
+                name("shift_remove_index"),
+                |set: &mut Self, index: usize| set.shift_remove_index(index),
+            )?
+            .register_with_name(name("truncate"), |set: &mut Self, len: usize| {
+                set.truncate(len)
+            })?
+            .register_with_name(name("clear"), |set: &mut Self| set.clear())?
+            .register_with_name(
+                name("move_index"),
+                |set: &mut Self, from: usize, to: usize| {
+                    (from < set.len() && to < set.len()).then(|| set.move_index(from, to))
+                },
+            )?
+            .register_with_name(
+                name("swap_indices"),
+                |set: &mut Self, a: usize, b: usize| {
+                    (a < set.len() && b < set.len()).then(|| set.swap_indices(a, b))
+                },
+            )?
+            .register_with_name(name("reverse"), |set: &mut Self| set.reverse())?;
+        merge_functions(registry, functions)
+    }
+
+    /// Register the sorting methods of this set type in `registry`, as
+    /// [`DynamicFunction`]s named like those of
+    /// [`register_functions`][Self::register_functions].
+    ///
+    /// The registered methods are `sort` and `sort_unstable`. Like
+    /// [`register_functions`][Self::register_functions], nothing is registered if one
+    /// of their names is already taken.
+    ///
+    /// [`DynamicFunction`]: bevy_reflect::func::DynamicFunction
+    pub fn register_sort_functions(
+        registry: &mut FunctionRegistry,
+    ) -> Result<(), FunctionRegistrationError>
+    where
+        T: Ord,
+    {
+        let name = |method: &str| format!("{}::{}", Self::type_path(), method);
+        let mut functions = FunctionRegistry::default();
+        functions
+            .register_with_name(name("sort"), |set: &mut Self| set.sort())?
+            .register_with_name(name("sort_unstable"), |set: &mut Self| set.sort_unstable())?;
+        merge_functions(registry, functions)
+    }
+}
+
+fn cloned_entry<K: Clone, V: Clone>(entry: Option<(&K, &V)>) -> Option<(K, V)> {
+    entry.map(|(key, value)| (key.clone(), value.clone()))
+}
+
+/// Register all of `functions` in `registry`, or none of them if one of their names is
+/// already registered.
+fn merge_functions(
+    registry: &mut FunctionRegistry,
+    functions: FunctionRegistry,
+) -> Result<(), FunctionRegistrationError> {
+    let taken = functions
+        .iter()
+        .filter_map(DynamicFunction::name)
+        .find(|name| registry.contains(name));
+    if let Some(name) = taken {
+        return Err(FunctionRegistrationError::DuplicateName(name.clone()));
+    }
+    for function in functions.iter() {
+        registry.register(function.clone())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use crate::{IndexMap, IndexSet};
+
+use core::hash::{BuildHasher, Hash};
+
+use bevy_reflect::std_traits::ReflectDefault;
+use bevy_reflect::{FromReflect, FromType, PartialReflect, Reflect, TypeRegistration};
+
+/// A hasher that reflected [`IndexMap`]s and [`IndexSet`]s can create on their own.
+///
+/// [`FromReflect`] and [`PartialReflect::reflect_clone`] build a new collection without any
+/// hasher to start from, so the reflection impls require the hasher to implement this trait.
+/// It is implemented for every [`Default`] hasher, which creates its hashers with
+/// [`Default::default`], and can be implemented for hashers that are not `Default`, like
+/// seeded hashers whose seed is only known at runtime.
+///
+/// When no hasher can be created, [`FromReflect::from_reflect`] returns `None` and
+/// [`PartialReflect::reflect_clone`] returns an error. A hasher can still be supplied
+/// explicitly with [`IndexMap::from_reflect_with_hasher`] or
+/// [`IndexSet::from_reflect_with_hasher`].
+///
+/// Collections with a hasher that isn't `Default` are registered without the
+/// [`ReflectDefault`] type data, since they don't implement `Default` themselves.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_reflect::{FromReflect, PartialReflect, TypePath};
+/// use core::hash::BuildHasher;
+/// use core::sync::atomic::{AtomicU64, Ordering};
+/// use indexmap::reflect::BuildReflectHasher;
+/// use indexmap::IndexMap;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use std::hash::Hasher;
+///
+/// static SEED: AtomicU64 = AtomicU64::new(0);
+///
+/// #[derive(TypePath)]
+/// struct SeededState(u64);
+///
+/// impl BuildHasher for SeededState {
+///     // ...
+/// #   type Hasher = DefaultHasher;
+/// #   fn build_hasher(&self) -> DefaultHasher {
+/// #       let mut hasher = DefaultHasher::new();
+/// #       hasher.write_u64(self.0);
+/// #       hasher
+/// #   }
+/// }
+///
+/// impl BuildReflectHasher for SeededState {
+///     fn build_reflect_hasher() -> Option<Self> {
+///         Some(SeededState(SEED.load(Ordering::Relaxed)))
+///     }
+///
+///     fn clone_reflect_hasher(&self) -> Option<Self> {
+///         Some(SeededState(self.0))
+///     }
+/// }
+///
+/// SEED.store(42, Ordering::Relaxed);
+/// let mut map = IndexMap::with_hasher(SeededState(7));
+/// map.insert(1u32, 10u32);
+///
+/// let rebuilt = IndexMap::<u32, u32, SeededState>::from_reflect(&map).unwrap();
+/// assert_eq!(rebuilt.hasher().0, 42);
+///
+/// let cloned = map.reflect_clone().unwrap();
+/// let cloned = cloned.downcast::<IndexMap<u32, u32, SeededState>>().unwrap();
+/// assert_eq!(cloned.hasher().0, 7);
+/// ```
+pub trait BuildReflectHasher: BuildHasher + Sized {
+    /// Creates the hasher for a collection built from reflection, as by
+    /// [`FromReflect::from_reflect`].
+    ///
+    /// Returns `None` if no hasher can be created in the current context.
+    fn build_reflect_hasher() -> Option<Self>;
+
+    /// Creates the hasher for a reflected clone of a collection using `self`, as by
+    /// [`PartialReflect::reflect_clone`].
+    ///
+    /// The default implementation creates a new hasher with
+    /// [`build_reflect_hasher`][Self::build_reflect_hasher]. Hashers that can be copied
+    /// should override it to keep the state of the original collection.
+    fn clone_reflect_hasher(&self) -> Option<Self> {
+        Self::build_reflect_hasher()
+    }
+
+    /// Registers the type data of `IndexMap<K, V, Self>` that depends on the hasher.
+    #[doc(hidden)]
+    fn register_map_type_data<K, V>(_registration: &mut TypeRegistration)
+    where
+        IndexMap<K, V, Self>: Reflect,
+    {
+    }
+
+    /// Registers the type data of `IndexSet<T, Self>` that depends on the hasher.
+    #[doc(hidden)]
+    fn register_set_type_data<T>(_registration: &mut TypeRegistration)
+    where
+        IndexSet<T, Self>: Reflect,
+    {
+    }
+}
+
+impl<S> BuildReflectHasher for S
+where
+    S: BuildHasher + Default,
+{
+    fn build_reflect_hasher() -> Option<Self> {
+        Some(S::default())
+    }
+
+    fn register_map_type_data<K, V>(registration: &mut TypeRegistration)
+    where
+        IndexMap<K, V, Self>: Reflect,
+    {
+        registration.insert::<ReflectDefault>(FromType::<IndexMap<K, V, S>>::from_type());
+    }
+
+    fn register_set_type_data<T>(registration: &mut TypeRegistration)
+    where
+        IndexSet<T, Self>: Reflect,
+    {
+        registration.insert::<ReflectDefault>(FromType::<IndexSet<T, S>>::from_type());
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S>
+where
+    K: FromReflect + Eq + Hash,
+    V: FromReflect,
+    S: BuildHasher,
+{
+    /// Creates a map from a reflected map, using the given hasher.
+    ///
+    /// This is [`FromReflect::from_reflect`] for maps whose hasher can't be created by
+    /// [`BuildReflectHasher`], and it doesn't require the map itself to be reflected.
+    ///
+    /// Returns `None` if `reflect` is not a map, or if any of its keys or values can't
+    /// be converted. The entries are inserted in the iteration order of `reflect`.
+    pub fn from_reflect_with_hasher(reflect: &dyn PartialReflect, hasher: S) -> Option<Self> {
+        let ref_map = reflect.reflect_ref().as_map().ok()?;
+
+        let mut new_map = Self::with_capacity_and_hasher(ref_map.len(), hasher);
+        for (key, value) in ref_map.iter() {
+            let new_key = K::from_reflect(key)?;
+            let new_value = V::from_reflect(value)?;
+            new_map.insert(new_key, new_value);
+        }
+
+        Some(new_map)
+    }
+}
+
+impl<T, S> IndexSet<T, S>
+where
+    T: FromReflect + Eq + Hash,
+    S: BuildHasher,
+{
+    /// Creates a set from a reflected set, using the given hasher.
+    ///
+    /// See [`IndexMap::from_reflect_with_hasher`] for details.
+    pub fn from_reflect_with_hasher(reflect: &dyn PartialReflect, hasher: S) -> Option<Self> {
+        let ref_set = reflect.reflect_ref().as_set().ok()?;
+
+        let mut new_set = Self::with_capacity_and_hasher(ref_set.len(), hasher);
+        for value in ref_set.iter() {
+            let new_value = T::from_reflect(value)?;
+            new_set.insert(new_value);
+        }
+
+        Some(new_set)
+    }
+}
@@ -0,0 +1,568 @@
+use super::hasher::BuildReflectHasher;
+use crate::{IndexMap, IndexSet};
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::hash::Hash;
+
+use bevy_reflect::{
+    FromReflect, FromType, GetTypeRegistration, MaybeTyped, PartialReflect, TypePath,
+};
+
+/// Type data giving order-aware access to a reflected [`IndexMap`] or [`IndexSet`].
+///
+/// `bevy_reflect` only sees these collections through the [`Map`][bevy_reflect::Map] and
+/// [`Set`][bevy_reflect::Set] traits, which have no notion of position. This type data is
+/// registered for both collections, so positional operations can be performed on a
+/// `&mut dyn PartialReflect` without knowing the concrete key, value, or hasher types.
+///
+/// For maps, the "key" of an entry is the map key, and the entry also has a value.
+/// For sets, the "key" is the set value itself, and there is no separate value.
+///
+/// # Examples
+///
+/// ```
+/// use bevy_reflect::{PartialReflect, TypeRegistry};
+/// use indexmap::reflect::ReflectIndexed;
+/// use indexmap::IndexMap;
+///
+/// let mut registry = TypeRegistry::default();
+/// registry.register::<IndexMap<u32, u32>>();
+///
+/// let mut map: IndexMap<u32, u32> = (0..5).map(|i| (i, i * 10)).collect();
+/// let reflected: &mut dyn PartialReflect = &mut map;
+///
+/// let indexed = registry
+///     .get_type_data::<ReflectIndexed>(core::any::TypeId::of::<IndexMap<u32, u32>>())
+///     .unwrap();
+/// indexed.move_index(reflected, 0, 4).unwrap();
+/// assert_eq!(indexed.get_index_of(reflected, &0u32), Some(4));
+///
+/// let (key, value) = indexed.get_index_mut(reflected, 4).unwrap();
+/// assert_eq!(key.try_downcast_ref::<u32>(), Some(&0));
+/// value.unwrap().apply(&5u32);
+///
+/// assert_eq!(map[&0], 5);
+///
+/// assert!(map.keys().eq(&[1, 2, 3, 4, 0]));
+/// ```
+#[derive(Clone)]
+pub struct ReflectIndexed {
+    check_type: fn(&dyn PartialReflect) -> Result<(), ReflectIndexedError>,
+    get_index: GetIndexFn,
+    get_index_mut: GetIndexMutFn,
+    get_index_of: fn(&dyn PartialReflect, &dyn PartialReflect) -> Option<usize>,
+    move_index: fn(&mut dyn PartialReflect, usize, usize) -> Result<(), ReflectIndexedError>,
+    swap_indices: fn(&mut dyn PartialReflect, usize, usize) -> Result<(), ReflectIndexedError>,
+    shift_insert: ShiftInsertFn,
+    shift_remove_index: ShiftRemoveIndexFn,
+}
+
+/// A borrowed entry: the key (or set value), and the map value if any.
+type EntryRef<'a> = (&'a dyn PartialReflect, Option<&'a dyn PartialReflect>);
+
+/// A borrowed entry with a mutable map value.
+type EntryMut<'a> = (&'a dyn PartialReflect, Option<&'a mut dyn PartialReflect>);
+
+/// A removed entry: the key (or set value), and the map value if any.
+type RemovedEntry = (Box<dyn PartialReflect>, Option<Box<dyn PartialReflect>>);
+
+type GetIndexFn = for<'a> fn(&'a dyn PartialReflect, usize) -> Option<EntryRef<'a>>;
+
+type GetIndexMutFn = for<'a> fn(&'a mut dyn PartialReflect, usize) -> Option<EntryMut<'a>>;
+
+type ShiftInsertFn = fn(
+    &mut dyn PartialReflect,
+    usize,
+    Box<dyn PartialReflect>,
+    Option<Box<dyn PartialReflect>>,
+) -> Result<Option<Box<dyn PartialReflect>>, ReflectIndexedError>;
+
+type ShiftRemoveIndexFn =
+    fn(&mut dyn PartialReflect, usize) -> Result<RemovedEntry, ReflectIndexedError>;
+
+impl ReflectIndexed {
+    /// Returns an error if `collection` is not the type this data was registered for.
+    pub(super) fn check_type(
+        &self,
+        collection: &dyn PartialReflect,
+    ) -> Result<(), ReflectIndexedError> {
+        (self.check_type)(collection)
+    }
+
+    /// Get the key and the value of the entry at `index` for maps, or the value
+    /// (with no separate value) for sets.
+    ///
+    /// Returns `None` if `index` is out of bounds, or if `collection` is not
+    /// the type this data was registered for.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index<'a>(
+        &self,
+        collection: &'a dyn PartialReflect,
+        index: usize,
+    ) -> Option<EntryRef<'a>> {
+        (self.get_index)(collection, index)
+    }
+
+    /// Get the key and a mutable reference to the value of the entry at `index` for
+    /// maps, or the value (with no separate value) for sets.
+    ///
+    /// Keys and set values can't be modified in place, since that could change their
+    /// hash.
+    ///
+    /// Returns `None` if `index` is out of bounds, or if `collection` is not
+    /// the type this data was registered for.
+    ///
+    /// Computes in **O(1)** time.
+    pub fn get_index_mut<'a>(
+        &self,
+        collection: &'a mut dyn PartialReflect,
+        index: usize,
+    ) -> Option<EntryMut<'a>> {
+        (self.get_index_mut)(collection, index)
+    }
+
+    /// Return the index of the entry with a key equal to `key`, if it exists.
+    ///
+    /// Returns `None` if `collection` is not the type this data was registered for.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn get_index_of(
+        &self,
+        collection: &dyn PartialReflect,
+        key: &dyn PartialReflect,
+    ) -> Option<usize> {
+        (self.get_index_of)(collection, key)
+    }
+
+    /// Moves the position of an entry from one index to another
+    /// by shifting all other entries in-between.
+    ///
+    /// This is the same as [`IndexMap::move_index`], except that out of bounds
+    /// indices are reported as an error instead of a panic.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn move_index(
+        &self,
+        collection: &mut dyn PartialReflect,
+        from: usize,
+        to: usize,
+    ) -> Result<(), ReflectIndexedError> {
+        (self.move_index)(collection, from, to)
+    }
+
+    /// Swaps the position of two entries.
+    ///
+    /// This is the same as [`IndexMap::swap_indices`], except that out of bounds
+    /// indices are reported as an error instead of a panic.
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn swap_indices(
+        &self,
+        collection: &mut dyn PartialReflect,
+        a: usize,
+        b: usize,
+    ) -> Result<(), ReflectIndexedError> {
+        (self.swap_indices)(collection, a, b)
+    }
+
+    /// Insert an entry at the given index, or move an existing entry there.
+    ///
+    /// Maps require a `value`, and return the previous value of an existing key
+    /// inside `Some(_)`. Sets must not be given a `value`, and always return `None`.
+    ///
+    /// This is the same as [`IndexMap::shift_insert`], so existing entries can only be
+    /// moved to `0..len`, while new entries can be inserted at `0..=len`. Out of bounds
+    /// indices are reported as an error instead of a panic.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_insert(
+        &self,
+        collection: &mut dyn PartialReflect,
+        index: usize,
+        key: Box<dyn PartialReflect>,
+        value: Option<Box<dyn PartialReflect>>,
+    ) -> Result<Option<Box<dyn PartialReflect>>, ReflectIndexedError> {
+        (self.shift_insert)(collection, index, key, value)
+    }
+
+    /// Remove the entry at `index` by shifting all of the entries that follow it,
+    /// and return its key along with the value for maps.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_remove_index(
+        &self,
+        collection: &mut dyn PartialReflect,
+        index: usize,
+    ) -> Result<RemovedEntry, ReflectIndexedError> {
+        (self.shift_remove_index)(collection, index)
+    }
+}
+
+impl<K, V, S> FromType<IndexMap<K, V, S>> for ReflectIndexed
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    fn from_type() -> Self {
+        ReflectIndexed {
+            check_type: check_type::<IndexMap<K, V, S>>,
+            get_index: map_get_index::<K, V, S>,
+            get_index_mut: map_get_index_mut::<K, V, S>,
+            get_index_of: map_get_index_of::<K, V, S>,
+            move_index: map_move_index::<K, V, S>,
+            swap_indices: map_swap_indices::<K, V, S>,
+            shift_insert: map_shift_insert::<K, V, S>,
+            shift_remove_index: map_shift_remove_index::<K, V, S>,
+        }
+    }
+}
+
+impl<T, S> FromType<IndexSet<T, S>> for ReflectIndexed
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    fn from_type() -> Self {
+        ReflectIndexed {
+            check_type: check_type::<IndexSet<T, S>>,
+            get_index: set_get_index::<T, S>,
+            get_index_mut: set_get_index_mut::<T, S>,
+            get_index_of: set_get_index_of::<T, S>,
+            move_index: set_move_index::<T, S>,
+            swap_indices: set_swap_indices::<T, S>,
+            shift_insert: set_shift_insert::<T, S>,
+            shift_remove_index: set_shift_remove_index::<T, S>,
+        }
+    }
+}
+
+/// The error type for the [`ReflectIndexed`] methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReflectIndexedError {
+    /// The reflected collection is not the type the [`ReflectIndexed`] was created for.
+    MismatchedType {
+        /// The type path of the collection that was expected.
+        expected: &'static str,
+        /// The type path of the value that was given.
+        received: String,
+    },
+    /// An index provided was out-of-bounds for the collection.
+    IndexOutOfBounds {
+        /// The index that was given.
+        index: usize,
+        /// The length of the collection.
+        len: usize,
+    },
+    /// A key (or set value) could not be converted to the collection's key type.
+    InvalidKey {
+        /// The type path of the key that was given.
+        received: String,
+    },
+    /// A map value could not be converted to the map's value type.
+    InvalidValue {
+        /// The type path of the value that was given.
+        received: String,
+    },
+    /// A map insertion was attempted without a value.
+    MissingValue,
+    /// A set insertion was attempted with a separate value.
+    UnexpectedValue,
+}
+
+impl fmt::Display for ReflectIndexedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReflectIndexedError::MismatchedType { expected, received } => {
+                write!(
+                    f,
+                    "expected a value of type `{expected}` but received `{received}`"
+                )
+            }
+            ReflectIndexedError::IndexOutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "index out of bounds: the len is {len} but the index is {index}"
+                )
+            }
+            ReflectIndexedError::InvalidKey { received } => {
+                write!(f, "attempted to use an invalid key of type `{received}`")
+            }
+            ReflectIndexedError::InvalidValue { received } => {
+                write!(f, "attempted to use an invalid value of type `{received}`")
+            }
+            ReflectIndexedError::MissingValue => f.write_str("a map entry requires a value"),
+            ReflectIndexedError::UnexpectedValue => {
+                f.write_str("a set entry does not have a separate value")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for ReflectIndexedError {}
+
+fn downcast_mut<T: TypePath + Send + Sync>(
+    collection: &mut dyn PartialReflect,
+) -> Result<&mut T, ReflectIndexedError> {
+    let received = collection.reflect_type_path().to_string();
+    collection
+        .try_downcast_mut::<T>()
+        .ok_or(ReflectIndexedError::MismatchedType {
+            expected: T::type_path(),
+            received,
+        })
+}
+
+fn check_type<T: TypePath + Send + Sync>(
+    collection: &dyn PartialReflect,
+) -> Result<(), ReflectIndexedError> {
+    match collection.try_downcast_ref::<T>() {
+        Some(_) => Ok(()),
+        None => Err(ReflectIndexedError::MismatchedType {
+            expected: T::type_path(),
+            received: collection.reflect_type_path().to_string(),
+        }),
+    }
+}
+
+fn check_index(index: usize, len: usize) -> Result<(), ReflectIndexedError> {
+    if index < len {
+        Ok(())
+    } else {
+        Err(ReflectIndexedError::IndexOutOfBounds { index, len })
+    }
+}
+
+/// Find the key in the collection, converting it with `FromReflect` if it's not a `K` already.
+pub(super) fn index_of<K, F>(key: &dyn PartialReflect, get_index_of: F) -> Option<usize>
+where
+    K: FromReflect,
+    F: FnOnce(&K) -> Option<usize>,
+{
+    match key.try_downcast_ref::<K>() {
+        Some(key) => get_index_of(key),
+        None => get_index_of(&K::from_reflect(key)?),
+    }
+}
+
+fn take_key<K: FromReflect>(key: Box<dyn PartialReflect>) -> Result<K, ReflectIndexedError> {
+    K::take_from_reflect(key).map_err(|key| ReflectIndexedError::InvalidKey {
+        received: key.reflect_type_path().to_string(),
+    })
+}
+
+fn map_get_index<K, V, S>(map: &dyn PartialReflect, index: usize) -> Option<EntryRef<'_>>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let map = map.try_downcast_ref::<IndexMap<K, V, S>>()?;
+    let (key, value) = map.get_index(index)?;
+    Some((key, Some(value)))
+}
+
+fn map_get_index_mut<K, V, S>(map: &mut dyn PartialReflect, index: usize) -> Option<EntryMut<'_>>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let map = map.try_downcast_mut::<IndexMap<K, V, S>>()?;
+    let (key, value) = map.get_index_mut(index)?;
+    Some((key, Some(value)))
+}
+
+fn map_get_index_of<K, V, S>(map: &dyn PartialReflect, key: &dyn PartialReflect) -> Option<usize>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let map = map.try_downcast_ref::<IndexMap<K, V, S>>()?;
+    index_of(key, |key: &K| map.get_index_of(key))
+}
+
+fn map_move_index<K, V, S>(
+    map: &mut dyn PartialReflect,
+    from: usize,
+    to: usize,
+) -> Result<(), ReflectIndexedError>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let map = downcast_mut::<IndexMap<K, V, S>>(map)?;
+    check_index(from, map.len())?;
+    check_index(to, map.len())?;
+    map.move_index(from, to);
+    Ok(())
+}
+
+fn map_swap_indices<K, V, S>(
+    map: &mut dyn PartialReflect,
+    a: usize,
+    b: usize,
+) -> Result<(), ReflectIndexedError>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let map = downcast_mut::<IndexMap<K, V, S>>(map)?;
+    check_index(a, map.len())?;
+    check_index(b, map.len())?;
+    map.swap_indices(a, b);
+    Ok(())
+}
+
+fn map_shift_insert<K, V, S>(
+    map: &mut dyn PartialReflect,
+    index: usize,
+    key: Box<dyn PartialReflect>,
+    value: Option<Box<dyn PartialReflect>>,
+) -> Result<Option<Box<dyn PartialReflect>>, ReflectIndexedError>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let map = downcast_mut::<IndexMap<K, V, S>>(map)?;
+    let value = value.ok_or(ReflectIndexedError::MissingValue)?;
+    let key = take_key::<K>(key)?;
+    let value = V::take_from_reflect(value).map_err(|value| ReflectIndexedError::InvalidValue {
+        received: value.reflect_type_path().to_string(),
+    })?;
+
+    // Existing entries can only move within `0..len`, new ones may also go at `len`.
+    let len = map.len();
+    let max = if map.contains_key(&key) { len } else { len + 1 };
+    check_index(index, max)?;
+
+    Ok(map
+        .shift_insert(index, key, value)
+        .map(|old| Box::new(old) as Box<dyn PartialReflect>))
+}
+
+fn map_shift_remove_index<K, V, S>(
+    map: &mut dyn PartialReflect,
+    index: usize,
+) -> Result<RemovedEntry, ReflectIndexedError>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let map = downcast_mut::<IndexMap<K, V, S>>(map)?;
+    let len = map.len();
+    match map.shift_remove_index(index) {
+        Some((key, value)) => Ok((Box::new(key), Some(Box::new(value)))),
+        None => Err(ReflectIndexedError::IndexOutOfBounds { index, len }),
+    }
+}
+
+fn set_get_index<T, S>(set: &dyn PartialReflect, index: usize) -> Option<EntryRef<'_>>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let set = set.try_downcast_ref::<IndexSet<T, S>>()?;
+    Some((set.get_index(index)?, None))
+}
+
+fn set_get_index_mut<T, S>(set: &mut dyn PartialReflect, index: usize) -> Option<EntryMut<'_>>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let set = set.try_downcast_ref::<IndexSet<T, S>>()?;
+    Some((set.get_index(index)?, None))
+}
+
+fn set_get_index_of<T, S>(set: &dyn PartialReflect, value: &dyn PartialReflect) -> Option<usize>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let set = set.try_downcast_ref::<IndexSet<T, S>>()?;
+    index_of(value, |value: &T| set.get_index_of(value))
+}
+
+fn set_move_index<T, S>(
+    set: &mut dyn PartialReflect,
+    from: usize,
+    to: usize,
+) -> Result<(), ReflectIndexedError>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let set = downcast_mut::<IndexSet<T, S>>(set)?;
+    check_index(from, set.len())?;
+    check_index(to, set.len())?;
+    set.move_index(from, to);
+    Ok(())
+}
+
+fn set_swap_indices<T, S>(
+    set: &mut dyn PartialReflect,
+    a: usize,
+    b: usize,
+) -> Result<(), ReflectIndexedError>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let set = downcast_mut::<IndexSet<T, S>>(set)?;
+    check_index(a, set.len())?;
+    check_index(b, set.len())?;
+    set.swap_indices(a, b);
+    Ok(())
+}
+
+fn set_shift_insert<T, S>(
+    set: &mut dyn PartialReflect,
+    index: usize,
+    value: Box<dyn PartialReflect>,
+    extra: Option<Box<dyn PartialReflect>>,
+) -> Result<Option<Box<dyn PartialReflect>>, ReflectIndexedError>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let set = downcast_mut::<IndexSet<T, S>>(set)?;
+    if extra.is_some() {
+        return Err(ReflectIndexedError::UnexpectedValue);
+    }
+    let value = take_key::<T>(value)?;
+
+    // Existing values can only move within `0..len`, new ones may also go at `len`.
+    let len = set.len();
+    let max = if set.contains(&value) { len } else { len + 1 };
+    check_index(index, max)?;
+
+    set.shift_insert(index, value);
+    Ok(None)
+}
+
+fn set_shift_remove_index<T, S>(
+    set: &mut dyn PartialReflect,
+    index: usize,
+) -> Result<RemovedEntry, ReflectIndexedError>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq + Hash,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    let set = downcast_mut::<IndexSet<T, S>>(set)?;
+    let len = set.len();
+    match set.shift_remove_index(index) {
+        Some(value) => Ok((Box::new(value), None)),
+        None => Err(ReflectIndexedError::IndexOutOfBounds { index, len }),
+    }
+}
@@ -0,0 +1,352 @@
+use super::dynamic::clone_key;
+use super::{BuildReflectHasher, ReflectIndexed, ReflectIndexedError};
+use crate::IndexMap;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::hash::Hash;
+
+use bevy_reflect::{
+    FromReflect, FromType, GetTypeRegistration, Map, MaybeTyped, PartialReflect, Reflect, TypePath,
+};
+
+/// An ordered structural diff between two [`IndexMap`]s, created by [`IndexMap::diff`].
+///
+/// Reflected maps can be diffed and patched without knowing their concrete type through
+/// [`ReflectIndexed::diff`] and [`ReflectIndexed::apply_patch`], which work with a
+/// [`DynamicIndexMapPatch`].
+///
+/// Applying the patch with [`IndexMap::apply_patch`] to the map it was created from turns
+/// it into the target map, with the same entries at the same positions. The patch is
+/// itself reflected, so it can be stored and serialized like any other reflected value,
+/// for example as the history of an undo/redo system.
+///
+/// The changes are applied in this order:
+///
+/// 1. The [`removed`][Self::removed] keys are removed, keeping the order of the others.
+/// 2. The values of the [`changed`][Self::changed] keys are replaced.
+/// 3. The [`inserted`][Self::inserted] entries and the [`moved`][Self::moved] keys are
+///    put at their index in the target map, in increasing order of index.
+///
+/// Only the keys that are out of order relative to the other kept keys are moved, which
+/// is the smallest possible number of moves.
+///
+/// Values are compared with [`PartialReflect::reflect_partial_eq`], so they don't need to
+/// implement `PartialEq`.
+///
+/// # Examples
+///
+/// ```
+/// use indexmap::IndexMap;
+///
+/// let old: IndexMap<&str, u32> = [("a", 1), ("b", 2), ("c", 3)].into_iter().collect();
+/// let new: IndexMap<&str, u32> = [("c", 3), ("d", 4), ("a", 10)].into_iter().collect();
+///
+/// let patch = old.diff(&new);
+/// assert_eq!(patch.removed(), ["b"]);
+/// assert_eq!(patch.changed(), [("a", 10)]);
+/// assert_eq!(patch.inserted(), [(1, "d", 4)]);
+/// assert_eq!(patch.moved(), [("a", 2)]);
+///
+/// let mut map = old.clone();
+/// map.apply_patch(&patch);
+/// assert!(map.iter().eq(&new));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Reflect)]
+pub struct IndexMapPatch<K, V> {
+    removed: Vec<K>,
+    changed: Vec<(K, V)>,
+    inserted: Vec<(usize, K, V)>,
+    moved: Vec<(K, usize)>,
+}
+
+impl<K, V> IndexMapPatch<K, V> {
+    /// Return the keys removed by this patch.
+    pub fn removed(&self) -> &[K] {
+        &self.removed
+    }
+
+    /// Return the keys whose value is replaced by this patch, with their new value.
+    pub fn changed(&self) -> &[(K, V)] {
+        &self.changed
+    }
+
+    /// Return the entries inserted by this patch, with their index in the target map,
+    /// in increasing order of index.
+    pub fn inserted(&self) -> &[(usize, K, V)] {
+        &self.inserted
+    }
+
+    /// Return the keys moved by this patch, with their index in the target map,
+    /// in increasing order of index.
+    pub fn moved(&self) -> &[(K, usize)] {
+        &self.moved
+    }
+
+    /// Returns `true` if this patch doesn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.inserted.is_empty()
+            && self.moved.is_empty()
+    }
+}
+
+/// An [`IndexMapPatch`] of reflected keys and values, created by [`ReflectIndexed::diff`].
+pub type DynamicIndexMapPatch = IndexMapPatch<Box<dyn PartialReflect>, Box<dyn PartialReflect>>;
+
+impl ReflectIndexed {
+    /// Computes the patch that turns `map` into `other`, including the positions of
+    /// its entries.
+    ///
+    /// This is the reflected counterpart to [`IndexMap::diff`]. `other` must be the
+    /// type this data was registered for, while `map` can be any reflected map.
+    /// Keys and values are cloned into the patch with [`PartialReflect::reflect_clone`]
+    /// when possible, and as dynamic values otherwise.
+    ///
+    /// Computes in **O(n log n)** time (average).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bevy_reflect::{FromType, Map};
+    /// use indexmap::reflect::ReflectIndexed;
+    /// use indexmap::IndexMap;
+    ///
+    /// let indexed = <ReflectIndexed as FromType<IndexMap<u32, u32>>>::from_type();
+    /// let old: IndexMap<u32, u32> = [(1, 1), (2, 2), (3, 3)].into_iter().collect();
+    /// let new: IndexMap<u32, u32> = [(3, 3), (4, 4), (1, 10)].into_iter().collect();
+    ///
+    /// let patch = indexed.diff(&old, &new).unwrap();
+    /// assert_eq!(patch.removed().len(), 1);
+    /// assert_eq!(patch.moved()[0].1, 2);
+    ///
+    /// let mut map = old.clone();
+    /// indexed.apply_patch(&mut map, &patch).unwrap();
+    /// assert!(map.iter().eq(&new));
+    /// ```
+    pub fn diff(
+        &self,
+        map: &dyn Map,
+        other: &dyn Map,
+    ) -> Result<DynamicIndexMapPatch, ReflectIndexedError> {
+        let target = other.as_partial_reflect();
+        self.check_type(target)?;
+
+        let mut patch = IndexMapPatch {
+            removed: Vec::new(),
+            changed: Vec::new(),
+            inserted: Vec::new(),
+            moved: Vec::new(),
+        };
+
+        // The indices in `other` of the keys that are kept, in their current order.
+        let mut kept = Vec::with_capacity(map.len());
+        for (key, value) in map.iter() {
+            match self.get_index_of(target, key) {
+                Some(index) => {
+                    let (_, new_value) = other.get_at(index).expect("index is in bounds");
+                    if value.reflect_partial_eq(new_value) != Some(true) {
+                        patch.changed.push((clone_key(key), clone_key(new_value)));
+                    }
+                    kept.push(index);
+                }
+                None => patch.removed.push(clone_key(key)),
+            }
+        }
+
+        for (index, (key, value)) in other.iter().enumerate() {
+            if map.get(key).is_none() {
+                patch
+                    .inserted
+                    .push((index, clone_key(key), clone_key(value)));
+            }
+        }
+
+        // The longest run of kept keys that are already in order can stay in place,
+        // and every other kept key has to move.
+        let mut stay = longest_increasing(&kept).into_iter().peekable();
+        let mut moved = Vec::new();
+        for &index in &kept {
+            if stay.next_if_eq(&index).is_none() {
+                moved.push(index);
+            }
+        }
+        moved.sort_unstable();
+        patch.moved.extend(moved.into_iter().map(|index| {
+            let (key, _) = other.get_at(index).expect("index is in bounds");
+            (clone_key(key), index)
+        }));
+
+        Ok(patch)
+    }
+
+    /// Applies a patch created by [`diff`][Self::diff] to `map`, which must be the type
+    /// this data was registered for.
+    ///
+    /// This is the reflected counterpart to [`IndexMap::apply_patch`], and changes the
+    /// map in the same way.
+    ///
+    /// Computes in **O(n²)** time in the worst case, since entries are put in place
+    /// with [`move_index`][Self::move_index].
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key or value of the patch can't be converted to the map's key or
+    /// value type.
+    pub fn apply_patch(
+        &self,
+        map: &mut dyn Map,
+        patch: &DynamicIndexMapPatch,
+    ) -> Result<(), ReflectIndexedError> {
+        self.check_type(map.as_partial_reflect())?;
+
+        for key in &patch.removed {
+            map.remove(&**key);
+        }
+
+        for (key, value) in &patch.changed {
+            map.insert_boxed(clone_key(&**key), clone_key(&**value));
+        }
+
+        // New keys are appended and moved keys are sent to the back, so the keys in
+        // front are in their final order, then the others are put in place from the front.
+        let mut placed = Vec::with_capacity(patch.inserted.len() + patch.moved.len());
+        for (index, key, value) in &patch.inserted {
+            map.insert_boxed(clone_key(&**key), clone_key(&**value));
+            placed.push((*index, &**key));
+        }
+        for (key, index) in &patch.moved {
+            if let Some(from) = self.get_index_of(map.as_partial_reflect(), &**key) {
+                let last = map.len() - 1;
+                self.move_index(map.as_partial_reflect_mut(), from, last)?;
+                placed.push((*index, &**key));
+            }
+        }
+
+        placed.sort_by_key(|&(index, _)| index);
+        for (to, key) in placed {
+            if let Some(from) = self.get_index_of(map.as_partial_reflect(), key) {
+                let to = to.min(map.len() - 1);
+                self.move_index(map.as_partial_reflect_mut(), from, to)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<K, V, S> IndexMap<K, V, S>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq + Hash,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+    S: TypePath + BuildReflectHasher + Send + Sync,
+{
+    /// Computes the patch that turns this map into `other`, including the positions of
+    /// its entries.
+    ///
+    /// Keys that only shift along with the removed and inserted entries are not moved.
+    /// This is a typed wrapper around [`ReflectIndexed::diff`].
+    ///
+    /// Computes in **O(n log n)** time (average).
+    pub fn diff<S2>(&self, other: &IndexMap<K, V, S2>) -> IndexMapPatch<K, V>
+    where
+        S2: TypePath + BuildReflectHasher + Send + Sync,
+    {
+        let indexed = <ReflectIndexed as FromType<IndexMap<K, V, S2>>>::from_type();
+        let patch = indexed
+            .diff(self, other)
+            .expect("`other` is the registered map type");
+        IndexMapPatch {
+            removed: patch.removed.into_iter().map(take).collect(),
+            changed: patch
+                .changed
+                .into_iter()
+                .map(|(key, value)| (take(key), take(value)))
+                .collect(),
+            inserted: patch
+                .inserted
+                .into_iter()
+                .map(|(index, key, value)| (index, take(key), take(value)))
+                .collect(),
+            moved: patch
+                .moved
+                .into_iter()
+                .map(|(key, index)| (take(key), index))
+                .collect(),
+        }
+    }
+
+    /// Applies a patch created by [`diff`][Self::diff].
+    ///
+    /// Applied to the map the patch was created from, this reproduces the target map
+    /// exactly. Other maps get the same changes as far as they apply: keys that are
+    /// missing are not removed or moved, and indices past the end of the map are
+    /// clamped to it. This is a typed wrapper around [`ReflectIndexed::apply_patch`].
+    ///
+    /// Computes in **O(n²)** time in the worst case, since entries are put in place
+    /// with [`move_index`][Self::move_index].
+    pub fn apply_patch(&mut self, patch: &IndexMapPatch<K, V>) {
+        let patch = IndexMapPatch {
+            removed: patch.removed.iter().map(|key| clone_key(key)).collect(),
+            changed: patch
+                .changed
+                .iter()
+                .map(|(key, value)| (clone_key(key), clone_key(value)))
+                .collect(),
+            inserted: patch
+                .inserted
+                .iter()
+                .map(|(index, key, value)| (*index, clone_key(key), clone_key(value)))
+                .collect(),
+            moved: patch
+                .moved
+                .iter()
+                .map(|(key, index)| (clone_key(key), *index))
+                .collect(),
+        };
+        let indexed = <ReflectIndexed as FromType<Self>>::from_type();
+        indexed
+            .apply_patch(self, &patch)
+            .expect("`self` is the registered map type");
+    }
+}
+
+/// Convert a reflected key or value of a patch back to its concrete type.
+fn take<T: FromReflect>(value: Box<dyn PartialReflect>) -> T {
+    T::take_from_reflect(value).unwrap_or_else(|value| {
+        panic!(
+            "Attempted to convert invalid value of type {}.",
+            value.reflect_type_path()
+        )
+    })
+}
+
+/// Returns a longest strictly increasing subsequence of `indices`.
+///
+/// Computes in **O(n log n)** time.
+fn longest_increasing(indices: &[usize]) -> Vec<usize> {
+    // `tails[len]` is the position of the smallest last element of an increasing
+    // subsequence of length `len + 1`, and `prev` links each position to the one
+    // before it in its subsequence.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev = Vec::with_capacity(indices.len());
+    for (i, &index) in indices.iter().enumerate() {
+        let len = tails.partition_point(|&tail| indices[tail] < index);
+        prev.push(if len > 0 { Some(tails[len - 1]) } else { None });
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        result.push(indices[i]);
+        next = prev[i];
+    }
+    result.reverse();
+    result
+}
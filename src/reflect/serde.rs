@@ -0,0 +1,62 @@
+#![cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+
+use crate::{IndexMap, IndexSet};
+
+use ::serde::de::DeserializeOwned;
+use ::serde::Serialize;
+use bevy_reflect::{
+    FromReflect, GetTypeRegistration, ReflectDeserialize, ReflectSerialize, TypePath, TypeRegistry,
+};
+
+impl<K, V, S> IndexMap<K, V, S>
+where
+    Self: FromReflect + TypePath + GetTypeRegistration + Serialize + DeserializeOwned,
+{
+    /// Register this map type along with its [`ReflectSerialize`] and
+    /// [`ReflectDeserialize`] type data.
+    ///
+    /// With these, [`ReflectSerializer`] and [`TypedReflectDeserializer`] (and so scene
+    /// files) use the `serde` implementations of `IndexMap` directly, which keep the
+    /// insertion order, instead of going through the generic dynamic map path.
+    ///
+    /// The type data can't be part of the registration of every `IndexMap`, because that
+    /// would require all keys and values to be (de)serializable, so each map type that
+    /// needs it must be registered with this function.
+    ///
+    /// [`ReflectSerializer`]: bevy_reflect::serde::ReflectSerializer
+    /// [`TypedReflectDeserializer`]: bevy_reflect::serde::TypedReflectDeserializer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bevy_reflect::{ReflectSerialize, TypeRegistry};
+    /// use indexmap::IndexMap;
+    ///
+    /// let mut registry = TypeRegistry::default();
+    /// IndexMap::<String, u32>::register_reflect_serde(&mut registry);
+    ///
+    /// assert!(registry
+    ///     .get_type_data::<ReflectSerialize>(core::any::TypeId::of::<IndexMap<String, u32>>())
+    ///     .is_some());
+    /// ```
+    pub fn register_reflect_serde(registry: &mut TypeRegistry) {
+        registry.register::<Self>();
+        registry.register_type_data::<Self, ReflectSerialize>();
+        registry.register_type_data::<Self, ReflectDeserialize>();
+    }
+}
+
+impl<T, S> IndexSet<T, S>
+where
+    Self: FromReflect + TypePath + GetTypeRegistration + Serialize + DeserializeOwned,
+{
+    /// Register this set type along with its [`ReflectSerialize`] and
+    /// [`ReflectDeserialize`] type data.
+    ///
+    /// See [`IndexMap::register_reflect_serde`] for details.
+    pub fn register_reflect_serde(registry: &mut TypeRegistry) {
+        registry.register::<Self>();
+        registry.register_type_data::<Self, ReflectSerialize>();
+        registry.register_type_data::<Self, ReflectDeserialize>();
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(feature = "functions")]
+use bevy_reflect::func::args::Arg;
+#[cfg(feature = "functions")]
+use bevy_reflect::func::args::FromArg;
+#[cfg(feature = "functions")]
+use bevy_reflect::func::args::GetOwnership;
+#[cfg(feature = "functions")]
+use bevy_reflect::func::args::Ownership;
+#[cfg(feature = "functions")]
+use bevy_reflect::func::ArgError;
+#[cfg(feature = "functions")]
+use bevy_reflect::func::IntoReturn;
+#[cfg(feature = "functions")]
+use bevy_reflect::func::Return;
+#[cfg(feature = "functions")]
+use bevy_reflect::func::Return::Owned;
+use bevy_reflect::set_apply;
+use bevy_reflect::set_partial_eq;
+use bevy_reflect::set_try_apply;
+use bevy_reflect::utility::GenericTypeInfoCell;
+use bevy_reflect::ApplyError;
+use bevy_reflect::FromReflect;
+use bevy_reflect::FromType;
+use bevy_reflect::Generics;
+use bevy_reflect::GetTypeRegistration;
+use bevy_reflect::PartialReflect;
+use bevy_reflect::Reflect;
+use bevy_reflect::ReflectCloneError;
+use bevy_reflect::ReflectFromPtr;
+use bevy_reflect::ReflectFromReflect;
+use bevy_reflect::ReflectKind;
+use bevy_reflect::ReflectMut;
+use bevy_reflect::ReflectOwned;
+use bevy_reflect::ReflectRef;
+use bevy_reflect::Set;
+use bevy_reflect::SetInfo;
+use bevy_reflect::TypeInfo;
+use bevy_reflect::TypeParamInfo;
+use bevy_reflect::TypePath;
+use bevy_reflect::TypeRegistration;
+use bevy_reflect::TypeRegistry;
+use bevy_reflect::Typed;
+
+use super::impl_full_reflect;
+use super::impl_reflect_for_hashset;
+#[cfg(feature = "functions")]
+use super::{impl_from_arg, impl_function_traits, impl_get_ownership, impl_into_return};
+use super::{BuildReflectHasher, DynamicIndexSet, ReflectIndexed};
+use crate::IndexSet;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::hash::Hash;
+
+impl_reflect_for_hashset!(IndexSet<V, S>);
+#[cfg(feature = "functions")]
+impl_function_traits!(IndexSet<V, S>;
+    <
+
+        V: Hash + Eq + FromReflect + TypePath + GetTypeRegistration,
+
+        S: TypePath + BuildReflectHasher + Send + Sync
+
+    >
+);
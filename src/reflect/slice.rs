@@ -0,0 +1,561 @@
+use super::impl_full_reflect;
+use super::DynamicIndexMap;
+use super::DynamicIndexSet;
+use crate::{map, set, Bucket, HashValue};
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::mem;
+
+use bevy_reflect::std_traits::ReflectDefault;
+use bevy_reflect::utility::GenericTypeInfoCell;
+use bevy_reflect::utility::GenericTypePathCell;
+use bevy_reflect::{
+    map_apply, map_partial_eq, map_try_apply, set_apply, set_partial_eq, set_try_apply, ApplyError,
+    DynamicMap, DynamicSet, FromReflect, FromType, Generics, GetTypeRegistration, Map, MapInfo,
+    MapIter, MaybeTyped, PartialReflect, Reflect, ReflectCloneError, ReflectFromPtr,
+    ReflectFromReflect, ReflectKind, ReflectMut, ReflectOwned, ReflectRef, Set, SetInfo, TypeInfo,
+    TypeParamInfo, TypePath, TypeRegistration, TypeRegistry, Typed,
+};
+
+/// The hash given to entries built by `FromReflect`, which were not taken from a map or set.
+///
+/// Slices have no hash table, so this is fine as long as the hashes of a slice are never
+/// consulted. Converting a slice back into a map or set always rehashes its keys.
+const PLACEHOLDER_HASH: HashValue = HashValue(0);
+
+impl<K: TypePath, V: TypePath> TypePath for Box<map::Slice<K, V>> {
+    fn type_path() -> &'static str {
+        static CELL: GenericTypePathCell = GenericTypePathCell::new();
+        CELL.get_or_insert::<Self, _>(|| {
+            format!(
+                "alloc::boxed::Box<indexmap::map::Slice<{}, {}>>",
+                K::type_path(),
+                V::type_path()
+            )
+        })
+    }
+
+    fn short_type_path() -> &'static str {
+        static CELL: GenericTypePathCell = GenericTypePathCell::new();
+        CELL.get_or_insert::<Self, _>(|| {
+            format!(
+                "Box<Slice<{}, {}>>",
+                K::short_type_path(),
+                V::short_type_path()
+            )
+        })
+    }
+
+    fn type_ident() -> Option<&'static str> {
+        Some("Box")
+    }
+
+    fn crate_name() -> Option<&'static str> {
+        Some("alloc")
+    }
+
+    fn module_path() -> Option<&'static str> {
+        Some("alloc::boxed")
+    }
+}
+
+/// Boxed map slices have a fixed length, so `Map::insert_boxed` and `Map::remove` panic.
+///
+/// Applying another map only succeeds when it has no keys that the slice is missing.
+impl<K, V> Map for Box<map::Slice<K, V>>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+{
+    fn get(&self, key: &dyn PartialReflect) -> Option<&dyn PartialReflect> {
+        let mut from_reflect = None;
+        let key = key.try_downcast_ref::<K>().or_else(|| {
+            from_reflect = K::from_reflect(key);
+            from_reflect.as_ref()
+        })?;
+        map::Slice::iter(self)
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value as &dyn PartialReflect)
+    }
+
+    fn get_mut(&mut self, key: &dyn PartialReflect) -> Option<&mut dyn PartialReflect> {
+        let mut from_reflect = None;
+        let key = key.try_downcast_ref::<K>().or_else(|| {
+            from_reflect = K::from_reflect(key);
+            from_reflect.as_ref()
+        })?;
+        map::Slice::iter_mut(self)
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value as &mut dyn PartialReflect)
+    }
+
+    fn get_at(&self, index: usize) -> Option<(&dyn PartialReflect, &dyn PartialReflect)> {
+        self.get_index(index)
+            .map(|(key, value)| (key as &dyn PartialReflect, value as &dyn PartialReflect))
+    }
+
+    fn get_at_mut(
+        &mut self,
+        index: usize,
+    ) -> Option<(&dyn PartialReflect, &mut dyn PartialReflect)> {
+        self.get_index_mut(index)
+            .map(|(key, value)| (key as &dyn PartialReflect, value as &mut dyn PartialReflect))
+    }
+
+    fn len(&self) -> usize {
+        map::Slice::len(self)
+    }
+
+    fn iter(&self) -> MapIter<'_> {
+        MapIter::new(self)
+    }
+
+    fn drain(&mut self) -> Vec<(Box<dyn PartialReflect>, Box<dyn PartialReflect>)> {
+        mem::take(self)
+            .into_iter()
+            .map(|(key, value)| {
+                (
+                    Box::new(key) as Box<dyn PartialReflect>,
+                    Box::new(value) as Box<dyn PartialReflect>,
+                )
+            })
+            .collect()
+    }
+
+    fn to_dynamic_map(&self) -> DynamicMap {
+        let mut dynamic_map = DynamicMap::default();
+        dynamic_map.set_represented_type(self.get_represented_type_info());
+        for (key, value) in map::Slice::iter(self) {
+            dynamic_map.insert_boxed(Box::new(clone_key(key)), value.to_dynamic());
+        }
+        dynamic_map
+    }
+
+    fn insert_boxed(
+        &mut self,
+        _key: Box<dyn PartialReflect>,
+        _value: Box<dyn PartialReflect>,
+    ) -> Option<Box<dyn PartialReflect>> {
+        panic!(
+            "boxed slices are read-only through reflection: cannot insert a key into a map slice"
+        )
+    }
+
+    fn remove(&mut self, _key: &dyn PartialReflect) -> Option<Box<dyn PartialReflect>> {
+        panic!(
+            "boxed slices are read-only through reflection: cannot remove a key from a map slice"
+        )
+    }
+}
+
+impl<K, V> PartialReflect for Box<map::Slice<K, V>>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+{
+    fn get_represented_type_info(&self) -> Option<&'static TypeInfo> {
+        Some(<Self as Typed>::type_info())
+    }
+
+    #[inline]
+    fn into_partial_reflect(self: Box<Self>) -> Box<dyn PartialReflect> {
+        self
+    }
+
+    fn as_partial_reflect(&self) -> &dyn PartialReflect {
+        self
+    }
+
+    fn as_partial_reflect_mut(&mut self) -> &mut dyn PartialReflect {
+        self
+    }
+
+    fn try_into_reflect(self: Box<Self>) -> Result<Box<dyn Reflect>, Box<dyn PartialReflect>> {
+        Ok(self)
+    }
+
+    fn try_as_reflect(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn try_as_reflect_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+
+    fn reflect_kind(&self) -> ReflectKind {
+        ReflectKind::Map
+    }
+
+    fn reflect_ref(&self) -> ReflectRef<'_> {
+        ReflectRef::Map(self)
+    }
+
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
+        ReflectMut::Map(self)
+    }
+
+    fn reflect_owned(self: Box<Self>) -> ReflectOwned {
+        ReflectOwned::Map(self)
+    }
+
+    fn reflect_clone(&self) -> Result<Box<dyn Reflect>, ReflectCloneError> {
+        let mut entries = Vec::with_capacity(self.len());
+        for bucket in &self.entries {
+            let key = bucket.key.reflect_clone()?.take::<K>().map_err(|_| {
+                ReflectCloneError::FailedDowncast {
+                    expected: Cow::Borrowed(<K as TypePath>::type_path()),
+                    received: Cow::Owned(bucket.key.reflect_type_path().to_string()),
+                }
+            })?;
+            let value = bucket.value.reflect_clone()?.take::<V>().map_err(|_| {
+                ReflectCloneError::FailedDowncast {
+                    expected: Cow::Borrowed(<V as TypePath>::type_path()),
+                    received: Cow::Owned(bucket.value.reflect_type_path().to_string()),
+                }
+            })?;
+            entries.push(Bucket {
+                hash: bucket.hash,
+                key,
+                value,
+            });
+        }
+
+        Ok(Box::new(map::Slice::from_boxed(entries.into_boxed_slice())))
+    }
+
+    fn to_dynamic(&self) -> Box<dyn PartialReflect> {
+        Box::new(DynamicIndexMap::from_map(self))
+    }
+
+    fn reflect_partial_eq(&self, value: &dyn PartialReflect) -> Option<bool> {
+        map_partial_eq(self, value)
+    }
+
+    fn apply(&mut self, value: &dyn PartialReflect) {
+        map_apply(self, value);
+    }
+
+    fn try_apply(&mut self, value: &dyn PartialReflect) -> Result<(), ApplyError> {
+        map_try_apply(self, value)
+    }
+}
+
+impl_full_reflect!(
+    <K, V> for Box<map::Slice<K, V>>
+    where
+        K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq,
+        V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+);
+
+impl<K, V> Typed for Box<map::Slice<K, V>>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+{
+    fn type_info() -> &'static TypeInfo {
+        static CELL: GenericTypeInfoCell = GenericTypeInfoCell::new();
+        CELL.get_or_insert::<Self, _>(|| {
+            TypeInfo::Map(
+                MapInfo::new::<Self, K, V>().with_generics(Generics::from_iter([
+                    TypeParamInfo::new::<K>("K"),
+                    TypeParamInfo::new::<V>("V"),
+                ])),
+            )
+        })
+    }
+}
+
+impl<K, V> GetTypeRegistration for Box<map::Slice<K, V>>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+{
+    fn get_type_registration() -> TypeRegistration {
+        let mut registration = TypeRegistration::of::<Self>();
+        registration.insert::<ReflectFromPtr>(FromType::<Self>::from_type());
+        registration.insert::<ReflectFromReflect>(FromType::<Self>::from_type());
+        registration.insert::<ReflectDefault>(FromType::<Self>::from_type());
+        registration
+    }
+
+    fn register_type_dependencies(registry: &mut TypeRegistry) {
+        registry.register::<K>();
+        registry.register::<V>();
+    }
+}
+
+impl<K, V> FromReflect for Box<map::Slice<K, V>>
+where
+    K: FromReflect + MaybeTyped + TypePath + GetTypeRegistration + Eq,
+    V: FromReflect + MaybeTyped + TypePath + GetTypeRegistration,
+{
+    fn from_reflect(reflect: &dyn PartialReflect) -> Option<Self> {
+        let ref_map = reflect.reflect_ref().as_map().ok()?;
+        let mut entries: Vec<Bucket<K, V>> = Vec::with_capacity(ref_map.len());
+        for (key, value) in ref_map.iter() {
+            let key = K::from_reflect(key)?;
+            let value = V::from_reflect(value)?;
+            // Distinct reflected keys may convert to equal keys, so the later value replaces
+            // the earlier one in place, just like inserting into a map.
+            if let Some(bucket) = entries.iter_mut().find(|bucket| bucket.key == key) {
+                bucket.value = value;
+                continue;
+            }
+            entries.push(Bucket {
+                hash: PLACEHOLDER_HASH,
+                key,
+                value,
+            });
+        }
+        Some(map::Slice::from_boxed(entries.into_boxed_slice()))
+    }
+}
+
+impl<T: TypePath> TypePath for Box<set::Slice<T>> {
+    fn type_path() -> &'static str {
+        static CELL: GenericTypePathCell = GenericTypePathCell::new();
+        CELL.get_or_insert::<Self, _>(|| {
+            format!(
+                "alloc::boxed::Box<indexmap::set::Slice<{}>>",
+                T::type_path()
+            )
+        })
+    }
+
+    fn short_type_path() -> &'static str {
+        static CELL: GenericTypePathCell = GenericTypePathCell::new();
+        CELL.get_or_insert::<Self, _>(|| format!("Box<Slice<{}>>", T::short_type_path()))
+    }
+
+    fn type_ident() -> Option<&'static str> {
+        Some("Box")
+    }
+
+    fn crate_name() -> Option<&'static str> {
+        Some("alloc")
+    }
+
+    fn module_path() -> Option<&'static str> {
+        Some("alloc::boxed")
+    }
+}
+
+/// Boxed set slices have a fixed length, so `Set::insert_boxed` and `Set::remove` panic.
+///
+/// Applying another set only succeeds when it has no values that the slice is missing.
+impl<T> Set for Box<set::Slice<T>>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq,
+{
+    fn get(&self, value: &dyn PartialReflect) -> Option<&dyn PartialReflect> {
+        let mut from_reflect = None;
+        let value = value.try_downcast_ref::<T>().or_else(|| {
+            from_reflect = T::from_reflect(value);
+            from_reflect.as_ref()
+        })?;
+        set::Slice::iter(self)
+            .find(|v| *v == value)
+            .map(|value| value as &dyn PartialReflect)
+    }
+
+    fn len(&self) -> usize {
+        set::Slice::len(self)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &dyn PartialReflect> + '_> {
+        let iter = set::Slice::iter(self).map(|v| v as &dyn PartialReflect);
+        Box::new(iter)
+    }
+
+    fn drain(&mut self) -> Vec<Box<dyn PartialReflect>> {
+        mem::take(self)
+            .into_iter()
+            .map(|value| Box::new(value) as Box<dyn PartialReflect>)
+            .collect()
+    }
+
+    fn to_dynamic_set(&self) -> DynamicSet {
+        let mut dynamic_set = DynamicSet::default();
+        dynamic_set.set_represented_type(self.get_represented_type_info());
+        for value in set::Slice::iter(self) {
+            dynamic_set.insert_boxed(Box::new(clone_key(value)));
+        }
+        dynamic_set
+    }
+
+    fn insert_boxed(&mut self, _value: Box<dyn PartialReflect>) -> bool {
+        panic!(
+            "boxed slices are read-only through reflection: cannot insert a value into a set slice"
+        )
+    }
+
+    fn remove(&mut self, _value: &dyn PartialReflect) -> bool {
+        panic!(
+            "boxed slices are read-only through reflection: cannot remove a value from a set slice"
+        )
+    }
+
+    fn contains(&self, value: &dyn PartialReflect) -> bool {
+        Set::get(self, value).is_some()
+    }
+}
+
+impl<T> PartialReflect for Box<set::Slice<T>>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq,
+{
+    fn get_represented_type_info(&self) -> Option<&'static TypeInfo> {
+        Some(<Self as Typed>::type_info())
+    }
+
+    #[inline]
+    fn into_partial_reflect(self: Box<Self>) -> Box<dyn PartialReflect> {
+        self
+    }
+
+    fn as_partial_reflect(&self) -> &dyn PartialReflect {
+        self
+    }
+
+    fn as_partial_reflect_mut(&mut self) -> &mut dyn PartialReflect {
+        self
+    }
+
+    fn try_into_reflect(self: Box<Self>) -> Result<Box<dyn Reflect>, Box<dyn PartialReflect>> {
+        Ok(self)
+    }
+
+    fn try_as_reflect(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn try_as_reflect_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+
+    fn apply(&mut self, value: &dyn PartialReflect) {
+        set_apply(self, value);
+    }
+
+    fn try_apply(&mut self, value: &dyn PartialReflect) -> Result<(), ApplyError> {
+        set_try_apply(self, value)
+    }
+
+    fn reflect_kind(&self) -> ReflectKind {
+        ReflectKind::Set
+    }
+
+    fn reflect_ref(&self) -> ReflectRef<'_> {
+        ReflectRef::Set(self)
+    }
+
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
+        ReflectMut::Set(self)
+    }
+
+    fn reflect_owned(self: Box<Self>) -> ReflectOwned {
+        ReflectOwned::Set(self)
+    }
+
+    fn reflect_clone(&self) -> Result<Box<dyn Reflect>, ReflectCloneError> {
+        let mut entries = Vec::with_capacity(self.len());
+        for bucket in &self.entries {
+            let value = bucket.key.reflect_clone()?.take::<T>().map_err(|_| {
+                ReflectCloneError::FailedDowncast {
+                    expected: Cow::Borrowed(<T as TypePath>::type_path()),
+                    received: Cow::Owned(bucket.key.reflect_type_path().to_string()),
+                }
+            })?;
+            entries.push(Bucket {
+                hash: bucket.hash,
+                key: value,
+                value: (),
+            });
+        }
+
+        Ok(Box::new(set::Slice::from_boxed(entries.into_boxed_slice())))
+    }
+
+    fn to_dynamic(&self) -> Box<dyn PartialReflect> {
+        Box::new(DynamicIndexSet::from_set(self))
+    }
+
+    fn reflect_partial_eq(&self, value: &dyn PartialReflect) -> Option<bool> {
+        set_partial_eq(self, value)
+    }
+}
+
+impl_full_reflect!(
+    <T> for Box<set::Slice<T>>
+    where
+        T: FromReflect + TypePath + GetTypeRegistration + Eq,
+);
+
+impl<T> Typed for Box<set::Slice<T>>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq,
+{
+    fn type_info() -> &'static TypeInfo {
+        static CELL: GenericTypeInfoCell = GenericTypeInfoCell::new();
+        CELL.get_or_insert::<Self, _>(|| {
+            TypeInfo::Set(
+                SetInfo::new::<Self, T>()
+                    .with_generics(Generics::from_iter([TypeParamInfo::new::<T>("T")])),
+            )
+        })
+    }
+}
+
+impl<T> GetTypeRegistration for Box<set::Slice<T>>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq,
+{
+    fn get_type_registration() -> TypeRegistration {
+        let mut registration = TypeRegistration::of::<Self>();
+        registration.insert::<ReflectFromPtr>(FromType::<Self>::from_type());
+        registration.insert::<ReflectFromReflect>(FromType::<Self>::from_type());
+        registration.insert::<ReflectDefault>(FromType::<Self>::from_type());
+        registration
+    }
+
+    fn register_type_dependencies(registry: &mut TypeRegistry) {
+        registry.register::<T>();
+    }
+}
+
+impl<T> FromReflect for Box<set::Slice<T>>
+where
+    T: FromReflect + TypePath + GetTypeRegistration + Eq,
+{
+    fn from_reflect(reflect: &dyn PartialReflect) -> Option<Self> {
+        let ref_set = reflect.reflect_ref().as_set().ok()?;
+        let mut entries: Vec<Bucket<T, ()>> = Vec::with_capacity(ref_set.len());
+        for value in ref_set.iter() {
+            let value = T::from_reflect(value)?;
+            // Distinct reflected values may convert to equal values, so only the first is kept.
+            if entries.iter().any(|bucket| bucket.key == value) {
+                continue;
+            }
+            entries.push(Bucket {
+                hash: PLACEHOLDER_HASH,
+                key: value,
+                value: (),
+            });
+        }
+        Some(set::Slice::from_boxed(entries.into_boxed_slice()))
+    }
+}
+
+/// Clone a key into its concrete type, so it can be hashed by the dynamic collections.
+fn clone_key<K: FromReflect + TypePath>(key: &K) -> K {
+    K::from_reflect(key).unwrap_or_else(|| {
+        panic!(
+            "Attempted to clone invalid key of type {}.",
+            key.reflect_type_path()
+        )
+    })
+}
@@ -0,0 +1,1178 @@
+use super::{
+    BuildReflectHasher, DynamicIndexMap, DynamicIndexSet, IndexMapPatch, MapApplyMode,
+    ReflectIndexed, ReflectIndexedError, ReflectMapApply,
+};
+use crate::{map, set, IndexMap, IndexSet};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use bevy_reflect::std_traits::ReflectDefault;
+use bevy_reflect::{
+    ApplyError, DynamicMap, FromReflect, GetTypeRegistration, Map, PartialReflect, Reflect,
+    ReflectCloneError, ReflectFromReflect, Set, TypeInfo, TypePath, TypeRegistry,
+};
+use core::any::TypeId;
+use core::hash::{BuildHasher, Hasher};
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::collections::hash_map::DefaultHasher;
+use std::string::{String, ToString};
+
+fn assert_reflected_order(map: &IndexMap<u32, u32>) {
+    let reflected: &dyn Map = map;
+    assert_eq!(reflected.len(), map.len());
+    for (i, (key, value)) in map.iter().enumerate() {
+        let (r_key, r_value) = reflected.get_at(i).unwrap();
+        assert_eq!(r_key.try_downcast_ref::<u32>(), Some(key));
+        assert_eq!(r_value.try_downcast_ref::<u32>(), Some(value));
+    }
+    assert!(reflected.get_at(map.len()).is_none());
+}
+
+#[test]
+fn get_at_matches_order() {
+    let map: IndexMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    assert_reflected_order(&map);
+}
+
+#[test]
+fn get_at_after_shift_remove() {
+    let mut map: IndexMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    map.shift_remove(&3);
+    map.shift_remove(&0);
+    assert_reflected_order(&map);
+    let (key, _) = Map::get_at(&map, 0).unwrap();
+    assert_eq!(key.try_downcast_ref::<u32>(), Some(&1));
+}
+
+#[test]
+fn get_at_after_swap_remove() {
+    let mut map: IndexMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    map.swap_remove(&2);
+    assert_reflected_order(&map);
+    let (key, _) = Map::get_at(&map, 2).unwrap();
+    assert_eq!(key.try_downcast_ref::<u32>(), Some(&9));
+}
+
+#[test]
+fn get_at_after_move_index() {
+    let mut map: IndexMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    map.move_index(0, 9);
+    map.move_index(5, 1);
+    assert_reflected_order(&map);
+    let (key, _) = Map::get_at(&map, 9).unwrap();
+    assert_eq!(key.try_downcast_ref::<u32>(), Some(&0));
+}
+
+#[test]
+fn get_at_mut() {
+    let mut map: IndexMap<u32, u32> = (0..10).map(|i| (i, i * 10)).collect();
+    map.move_index(7, 0);
+    {
+        let (key, value) = Map::get_at_mut(&mut map, 0).unwrap();
+        assert_eq!(key.try_downcast_ref::<u32>(), Some(&7));
+        value.apply(&700u32);
+    }
+    assert_eq!(map[&7], 700);
+    assert_eq!(map.get_index_of(&7), Some(0));
+    assert!(Map::get_at_mut(&mut map, 10).is_none());
+}
+
+fn indexed<T: GetTypeRegistration>() -> ReflectIndexed {
+    let mut registry = TypeRegistry::default();
+    registry.register::<T>();
+    registry
+        .get_type_data::<ReflectIndexed>(TypeId::of::<T>())
+        .expect("`ReflectIndexed` should be registered")
+        .clone()
+}
+
+#[test]
+fn indexed_map() {
+    let indexed = indexed::<IndexMap<u32, String>>();
+    let mut map: IndexMap<u32, String> = (0..5).map(|i| (i, i.to_string())).collect();
+
+    let reflected: &mut dyn PartialReflect = &mut map;
+    let (key, value) = indexed.get_index(reflected, 3).unwrap();
+    assert_eq!(key.try_downcast_ref::<u32>(), Some(&3));
+    assert_eq!(value.unwrap().try_downcast_ref::<String>().unwrap(), "3");
+    assert_eq!(indexed.get_index_of(reflected, &4u32), Some(4));
+    assert!(indexed.get_index(reflected, 5).is_none());
+
+    let (key, value) = indexed.get_index_mut(reflected, 2).unwrap();
+    assert_eq!(key.try_downcast_ref::<u32>(), Some(&2));
+    value.unwrap().apply(&String::from("two"));
+    assert!(indexed.get_index_mut(reflected, 5).is_none());
+
+    indexed.move_index(reflected, 4, 0).unwrap();
+    indexed.swap_indices(reflected, 1, 2).unwrap();
+    let old = indexed
+        .shift_insert(
+            reflected,
+            2,
+            Box::new(9u32),
+            Some(Box::new(String::from("9"))),
+        )
+        .unwrap();
+    assert!(old.is_none());
+    let old = indexed
+        .shift_insert(
+            reflected,
+            0,
+            Box::new(3u32),
+            Some(Box::new(String::from("x"))),
+        )
+        .unwrap();
+    assert_eq!(old.unwrap().try_downcast_ref::<String>().unwrap(), "3");
+    let (key, value) = indexed.shift_remove_index(reflected, 1).unwrap();
+    assert_eq!(key.try_downcast_ref::<u32>(), Some(&4));
+    assert_eq!(value.unwrap().try_downcast_ref::<String>().unwrap(), "4");
+
+    assert!(map.keys().eq(&[3, 1, 9, 0, 2]));
+    assert_eq!(map[&3], "x");
+    assert_eq!(map[&2], "two");
+}
+
+#[test]
+fn indexed_map_errors() {
+    let indexed = indexed::<IndexMap<u32, String>>();
+    let mut map: IndexMap<u32, String> = (0..3).map(|i| (i, i.to_string())).collect();
+    let reflected: &mut dyn PartialReflect = &mut map;
+
+    let oob = ReflectIndexedError::IndexOutOfBounds { index: 3, len: 3 };
+    assert_eq!(indexed.move_index(reflected, 0, 3), Err(oob.clone()));
+    assert_eq!(indexed.swap_indices(reflected, 3, 0), Err(oob.clone()));
+    assert_eq!(
+        indexed.shift_remove_index(reflected, 3).err(),
+        Some(oob.clone())
+    );
+    // An existing key can't be moved to `len`, but a new one can be inserted there.
+    let existing = Box::new(String::from("0"));
+    assert_eq!(
+        indexed
+            .shift_insert(reflected, 3, Box::new(0u32), Some(existing))
+            .err(),
+        Some(oob)
+    );
+    assert!(indexed
+        .shift_insert(reflected, 3, Box::new(3u32), Some(Box::new(String::new())))
+        .is_ok());
+
+    assert_eq!(
+        indexed
+            .shift_insert(reflected, 0, Box::new(4u32), None)
+            .err(),
+        Some(ReflectIndexedError::MissingValue)
+    );
+    assert!(matches!(
+        indexed.shift_insert(reflected, 0, Box::new("4"), Some(Box::new(String::new()))),
+        Err(ReflectIndexedError::InvalidKey { .. })
+    ));
+
+    let mut other: IndexMap<u64, String> = IndexMap::new();
+    assert!(matches!(
+        indexed.move_index(&mut other, 0, 0),
+        Err(ReflectIndexedError::MismatchedType { .. })
+    ));
+    assert!(indexed.get_index(&other, 0).is_none());
+    assert!(indexed.get_index_mut(&mut other, 0).is_none());
+    assert_eq!(map.len(), 4);
+}
+
+#[test]
+fn indexed_set() {
+    let indexed = indexed::<IndexSet<u32>>();
+    let mut set: IndexSet<u32> = (0..5).collect();
+
+    let reflected: &mut dyn PartialReflect = &mut set;
+    let (value, extra) = indexed.get_index(reflected, 3).unwrap();
+    assert_eq!(value.try_downcast_ref::<u32>(), Some(&3));
+    assert!(extra.is_none());
+    let (value, extra) = indexed.get_index_mut(reflected, 3).unwrap();
+    assert_eq!(value.try_downcast_ref::<u32>(), Some(&3));
+    assert!(extra.is_none());
+    assert_eq!(indexed.get_index_of(reflected, &4u32), Some(4));
+
+    indexed.move_index(reflected, 4, 0).unwrap();
+    indexed.swap_indices(reflected, 1, 2).unwrap();
+    assert!(indexed
+        .shift_insert(reflected, 2, Box::new(9u32), None)
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        indexed
+            .shift_insert(reflected, 0, Box::new(7u32), Some(Box::new(7u32)))
+            .err(),
+        Some(ReflectIndexedError::UnexpectedValue)
+    );
+    let (value, extra) = indexed.shift_remove_index(reflected, 0).unwrap();
+    assert_eq!(value.try_downcast_ref::<u32>(), Some(&4));
+    assert!(extra.is_none());
+
+    assert!(set.iter().eq(&[1, 9, 0, 2, 3]));
+}
+
+#[test]
+fn dynamic_map_round_trip() {
+    let mut map: IndexMap<u32, String> = (0..10).map(|i| (i, i.to_string())).collect();
+    map.move_index(9, 0);
+    map.shift_remove(&4);
+
+    let dynamic = map.to_dynamic();
+    assert!(dynamic.is_dynamic());
+    assert_eq!(
+        dynamic.reflect_type_path(),
+        <DynamicIndexMap as TypePath>::type_path()
+    );
+    assert_eq!(
+        dynamic.get_represented_type_info().unwrap().type_id(),
+        TypeId::of::<IndexMap<u32, String>>()
+    );
+    let reflected = dynamic.reflect_ref().as_map().unwrap();
+    for (i, key) in map.keys().enumerate() {
+        let (r_key, _) = reflected.get_at(i).unwrap();
+        assert_eq!(r_key.try_downcast_ref::<u32>(), Some(key));
+    }
+
+    let cloned = IndexMap::<u32, String>::from_reflect(&*dynamic).unwrap();
+    assert!(cloned.keys().eq(map.keys()));
+    assert_eq!(map.reflect_partial_eq(&*dynamic), Some(true));
+
+    let dynamic = DynamicIndexMap::from_map(&map);
+    for (i, key) in map.keys().enumerate() {
+        assert_eq!(dynamic.get_index_of(key), Some(i));
+    }
+}
+
+#[test]
+fn dynamic_map_order_after_removals() {
+    let mut dynamic: DynamicIndexMap = (0..6u32)
+        .map(|i| {
+            (
+                Box::new(i) as Box<dyn PartialReflect>,
+                Box::new(i * 10) as Box<dyn PartialReflect>,
+            )
+        })
+        .collect();
+    dynamic.remove(&1u32);
+    dynamic.shift_remove_index(0);
+    dynamic.move_index(3, 0);
+    dynamic.swap_indices(1, 2);
+    dynamic.shift_insert(1, Box::new(9u32), Box::new(90u32));
+    dynamic.insert(2u32, 20u32);
+    dynamic.insert(7u32, 70u32);
+
+    let mut map: IndexMap<u32, u32> = IndexMap::new();
+    map.apply(&dynamic);
+    assert!(map.keys().eq(&[5, 9, 3, 2, 4, 7]));
+    assert_eq!(map[&2], 20);
+
+    let mut cloned = dynamic.to_dynamic();
+    let drained: Vec<u32> = cloned
+        .reflect_mut()
+        .as_map()
+        .unwrap()
+        .drain()
+        .into_iter()
+        .map(|(key, _)| *key.try_downcast_ref::<u32>().unwrap())
+        .collect();
+    assert_eq!(drained, [5, 9, 3, 2, 4, 7]);
+}
+
+#[test]
+fn dynamic_set_round_trip() {
+    let mut set: IndexSet<u32> = (0..10).collect();
+    set.move_index(0, 9);
+    set.shift_remove(&5);
+
+    let dynamic = set.to_dynamic();
+    assert_eq!(
+        dynamic.reflect_type_path(),
+        <DynamicIndexSet as TypePath>::type_path()
+    );
+    assert_eq!(IndexSet::<u32>::from_reflect(&*dynamic).unwrap(), set);
+
+    let mut dynamic = DynamicIndexSet::from_set(&set);
+    assert!(Set::iter(&dynamic)
+        .map(|v| v.try_downcast_ref::<u32>().unwrap())
+        .eq(&set));
+    assert!(dynamic.remove(&3u32));
+    dynamic.shift_insert(0, Box::new(3u32));
+    assert_eq!(dynamic.get_index_of(&3u32), Some(0));
+
+    let cloned = IndexSet::<u32>::from_reflect(&dynamic).unwrap();
+    assert!(cloned.iter().eq(&[3, 1, 2, 4, 6, 7, 8, 9, 0]));
+}
+
+fn apply_target() -> IndexMap<u32, String> {
+    (0..5).map(|i| (i, i.to_string())).collect()
+}
+
+fn apply_source_dynamic() -> DynamicMap {
+    let mut source = DynamicMap::default();
+    source.insert(3u32, String::from("x"));
+    source.insert(7u32, String::from("7"));
+    source.insert(1u32, String::from("y"));
+    source
+}
+
+fn apply_source_map() -> IndexMap<u32, String> {
+    [(3, "x"), (7, "7"), (1, "y")]
+        .into_iter()
+        .map(|(k, v)| (k, String::from(v)))
+        .collect()
+}
+
+fn assert_entries(map: &IndexMap<u32, String>, expected: &[(u32, &str)]) {
+    assert!(
+        map.iter()
+            .map(|(k, v)| (*k, v.as_str()))
+            .eq(expected.iter().copied()),
+        "{map:?}"
+    );
+}
+
+#[test]
+fn apply_merge_dynamic_map() {
+    let mut map = apply_target();
+    map.try_apply_with_mode(&apply_source_dynamic(), MapApplyMode::Merge)
+        .unwrap();
+    assert_entries(
+        &map,
+        &[(0, "0"), (1, "y"), (2, "2"), (3, "x"), (4, "4"), (7, "7")],
+    );
+}
+
+#[test]
+fn apply_merge_index_map() {
+    let mut map = apply_target();
+    map.try_apply_with_mode(&apply_source_map(), MapApplyMode::Merge)
+        .unwrap();
+    assert_entries(
+        &map,
+        &[(0, "0"), (1, "y"), (2, "2"), (3, "x"), (4, "4"), (7, "7")],
+    );
+
+    // The default `apply` merges too.
+    let mut other = apply_target();
+    other.apply(&apply_source_map());
+    assert_eq!(other.as_slice(), map.as_slice());
+}
+
+#[test]
+fn apply_mirror_order_dynamic_map() {
+    let mut map = apply_target();
+    map.try_apply_with_mode(&apply_source_dynamic(), MapApplyMode::MirrorOrder)
+        .unwrap();
+    assert_entries(
+        &map,
+        &[(3, "x"), (7, "7"), (1, "y"), (0, "0"), (2, "2"), (4, "4")],
+    );
+}
+
+#[test]
+fn apply_mirror_order_index_map() {
+    let mut map = apply_target();
+    map.try_apply_with_mode(&apply_source_map(), MapApplyMode::MirrorOrder)
+        .unwrap();
+    assert_entries(
+        &map,
+        &[(3, "x"), (7, "7"), (1, "y"), (0, "0"), (2, "2"), (4, "4")],
+    );
+}
+
+#[test]
+fn apply_replace_dynamic_map() {
+    let mut map = apply_target();
+    map.try_apply_with_mode(&apply_source_dynamic(), MapApplyMode::Replace)
+        .unwrap();
+    assert_entries(&map, &[(3, "x"), (7, "7"), (1, "y")]);
+}
+
+#[test]
+fn apply_replace_index_map() {
+    let mut map = apply_target();
+    map.try_apply_with_mode(&apply_source_map(), MapApplyMode::Replace)
+        .unwrap();
+    assert_entries(&map, &[(3, "x"), (7, "7"), (1, "y")]);
+}
+
+#[test]
+fn apply_mode_type_data() {
+    let mut registry = TypeRegistry::default();
+    registry.register::<IndexMap<u32, String>>();
+    let type_id = TypeId::of::<IndexMap<u32, String>>();
+
+    let apply = registry.get_type_data::<ReflectMapApply>(type_id).unwrap();
+    assert_eq!(apply.mode(), MapApplyMode::Merge);
+    registry
+        .get_type_data_mut::<ReflectMapApply>(type_id)
+        .unwrap()
+        .set_mode(MapApplyMode::MirrorOrder);
+
+    let apply = registry.get_type_data::<ReflectMapApply>(type_id).unwrap();
+    let mut map = apply_target();
+    apply.try_apply(&mut map, &apply_source_dynamic()).unwrap();
+    assert_entries(
+        &map,
+        &[(3, "x"), (7, "7"), (1, "y"), (0, "0"), (2, "2"), (4, "4")],
+    );
+
+    let mut other: IndexMap<u64, String> = IndexMap::new();
+    assert!(matches!(
+        apply.try_apply(&mut other, &apply_source_dynamic()),
+        Err(ApplyError::MismatchedTypes { .. })
+    ));
+    assert!(matches!(
+        apply.try_apply(&mut map, &0u32),
+        Err(ApplyError::MismatchedKinds { .. })
+    ));
+}
+
+#[derive(Reflect)]
+struct Frozen {
+    map: Box<map::Slice<u32, String>>,
+    set: Box<set::Slice<u32>>,
+}
+
+#[test]
+fn slice_type_info() {
+    let mut registry = TypeRegistry::default();
+    registry.register::<Frozen>();
+
+    let map_info = registry
+        .get_type_info(TypeId::of::<Box<map::Slice<u32, String>>>())
+        .unwrap();
+    assert_eq!(
+        map_info.type_path(),
+        "alloc::boxed::Box<indexmap::map::Slice<u32, alloc::string::String>>"
+    );
+    assert_eq!(
+        map_info.type_path_table().short_path(),
+        "Box<Slice<u32, String>>"
+    );
+    assert!(map_info.as_map().unwrap().key_ty().is::<u32>());
+
+    let set_info = registry
+        .get_type_info(TypeId::of::<Box<set::Slice<u32>>>())
+        .unwrap();
+    assert_eq!(
+        set_info.type_path(),
+        "alloc::boxed::Box<indexmap::set::Slice<u32>>"
+    );
+    match set_info {
+        TypeInfo::Set(info) => assert!(info.value_ty().is::<u32>()),
+        _ => panic!("expected a set"),
+    }
+}
+
+#[test]
+fn map_slice_reflect() {
+    let map: IndexMap<u32, String> = (0..5).rev().map(|i| (i, i.to_string())).collect();
+    let mut frozen = Frozen {
+        map: map.clone().into_boxed_slice(),
+        set: IndexSet::new().into_boxed_slice(),
+    };
+
+    let slice: &mut dyn Map = &mut frozen.map;
+    assert_eq!(slice.len(), 5);
+    let (key, value) = slice.get_at(1).unwrap();
+    assert_eq!(key.try_downcast_ref::<u32>(), Some(&3));
+    assert_eq!(value.try_downcast_ref::<String>().unwrap(), "3");
+    assert_eq!(
+        slice
+            .get(&2u32)
+            .unwrap()
+            .try_downcast_ref::<String>()
+            .unwrap(),
+        "2"
+    );
+    *slice
+        .get_mut(&4u32)
+        .unwrap()
+        .try_downcast_mut::<String>()
+        .unwrap() = String::from("four");
+    assert!(frozen.map.keys().eq(&[4, 3, 2, 1, 0]));
+    assert_eq!(frozen.map[0], "four");
+
+    frozen.apply(&Frozen {
+        map: map.clone().into_boxed_slice(),
+        set: IndexSet::new().into_boxed_slice(),
+    });
+    assert_eq!(*frozen.map, *map.as_slice());
+
+    let cloned = frozen.map.reflect_clone().unwrap();
+    assert_eq!(cloned.reflect_partial_eq(&frozen.map), Some(true));
+
+    let from_map = <Box<map::Slice<u32, String>>>::from_reflect(&map).unwrap();
+    assert_eq!(*from_map, *map.as_slice());
+    let back = IndexMap::<u32, String>::from_reflect(&from_map).unwrap();
+    assert!(back.iter().eq(&map));
+}
+
+#[test]
+fn set_slice_reflect() {
+    let set: IndexSet<u32> = (0..5).rev().collect();
+    let mut frozen = Frozen {
+        map: IndexMap::new().into_boxed_slice(),
+        set: set.clone().into_boxed_slice(),
+    };
+
+    let slice: &mut dyn Set = &mut frozen.set;
+    assert!(slice.contains(&2u32));
+    assert!(!slice.contains(&9u32));
+    assert_eq!(
+        slice.get(&2u32).unwrap().try_downcast_ref::<u32>(),
+        Some(&2)
+    );
+
+    frozen.apply(&Frozen {
+        map: IndexMap::new().into_boxed_slice(),
+        set: set
+            .iter()
+            .rev()
+            .copied()
+            .collect::<IndexSet<u32>>()
+            .into_boxed_slice(),
+    });
+    assert!((*frozen.set).iter().eq(&[4, 3, 2, 1, 0]));
+
+    let from_set = <Box<set::Slice<u32>>>::from_reflect(&set).unwrap();
+    assert_eq!(*from_set, *set.as_slice());
+}
+
+#[test]
+#[should_panic(expected = "boxed slices are read-only through reflection")]
+fn map_slice_insert_panics() {
+    let mut slice: Box<map::Slice<u32, u32>> = IndexMap::new().into_boxed_slice();
+    slice.insert_boxed(Box::new(1u32), Box::new(1u32));
+}
+
+#[test]
+#[should_panic(expected = "boxed slices are read-only through reflection")]
+fn set_slice_remove_panics() {
+    let mut slice: Box<set::Slice<u32>> = IndexSet::from([1u32]).into_boxed_slice();
+    Set::remove(&mut slice, &1u32);
+}
+
+/// A key whose `FromReflect` drops its ignored field, so distinct keys can convert to equal ones.
+#[derive(Reflect, PartialEq, Eq, Hash)]
+#[reflect(Hash, PartialEq)]
+struct Folded(#[reflect(ignore)] u32, u32);
+
+#[test]
+fn slice_from_reflect_dedup() {
+    let map: IndexMap<Folded, u32> = [(Folded(1, 5), 1), (Folded(0, 6), 2), (Folded(2, 5), 3)]
+        .into_iter()
+        .collect();
+    let slice = <Box<map::Slice<Folded, u32>>>::from_reflect(&map).unwrap();
+    assert!(map::Slice::iter(&slice)
+        .map(|(key, &value)| (key.1, value))
+        .eq([(5, 3), (6, 2)]));
+
+    let set: IndexSet<Folded> = [Folded(1, 5), Folded(0, 6), Folded(2, 5)]
+        .into_iter()
+        .collect();
+    let slice = <Box<set::Slice<Folded>>>::from_reflect(&set).unwrap();
+    assert!(set::Slice::iter(&slice).map(|key| key.1).eq([5, 6]));
+}
+
+#[test]
+fn slice_get_from_reflect() {
+    let slice: Box<map::Slice<(u32, u32), u32>> =
+        IndexMap::from([((1u32, 2u32), 3u32)]).into_boxed_slice();
+    let key = (1u32, 2u32).to_dynamic();
+    assert_eq!(
+        Map::get(&slice, &*key).unwrap().try_downcast_ref::<u32>(),
+        Some(&3)
+    );
+
+    let slice: Box<set::Slice<(u32, u32)>> = IndexSet::from([(1u32, 2u32)]).into_boxed_slice();
+    assert!(Set::contains(&slice, &*key));
+}
+
+static SEED: AtomicU64 = AtomicU64::new(0);
+
+/// A hasher without `Default`, which gets its seed from `SEED` when built by reflection.
+#[derive(TypePath)]
+struct SeededState(u64);
+
+impl BuildHasher for SeededState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.0);
+        hasher
+    }
+}
+
+impl BuildReflectHasher for SeededState {
+    fn build_reflect_hasher() -> Option<Self> {
+        Some(SeededState(SEED.load(Ordering::Relaxed)))
+    }
+
+    fn clone_reflect_hasher(&self) -> Option<Self> {
+        Some(SeededState(self.0))
+    }
+}
+
+/// A hasher that can't be built by reflection at all.
+#[derive(TypePath)]
+struct ExternalState(u64);
+
+impl BuildHasher for ExternalState {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
+        let mut hasher = DefaultHasher::new();
+        hasher.write_u64(self.0);
+        hasher
+    }
+}
+
+impl BuildReflectHasher for ExternalState {
+    fn build_reflect_hasher() -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Reflect)]
+struct Seeded {
+    map: IndexMap<u32, u32, SeededState>,
+    set: IndexSet<u32, SeededState>,
+}
+
+#[test]
+fn seeded_hasher_reflect() {
+    SEED.store(42, Ordering::Relaxed);
+    let mut seeded = Seeded {
+        map: IndexMap::with_hasher(SeededState(7)),
+        set: IndexSet::with_hasher(SeededState(7)),
+    };
+    seeded.map.extend((0..5).rev().map(|i| (i, i * 10)));
+    seeded.set.extend((0..5).rev());
+
+    let mut registry = TypeRegistry::default();
+    registry.register::<Seeded>();
+    let map_id = TypeId::of::<IndexMap<u32, u32, SeededState>>();
+    assert!(registry
+        .get_type_data::<ReflectFromReflect>(map_id)
+        .is_some());
+    assert!(registry.get_type_data::<ReflectDefault>(map_id).is_none());
+    let set_id = TypeId::of::<IndexSet<u32, SeededState>>();
+    assert!(registry
+        .get_type_data::<ReflectFromReflect>(set_id)
+        .is_some());
+    assert!(registry.get_type_data::<ReflectDefault>(set_id).is_none());
+    let default_id = TypeId::of::<IndexMap<u32, u32>>();
+    registry.register::<IndexMap<u32, u32>>();
+    assert!(registry
+        .get_type_data::<ReflectDefault>(default_id)
+        .is_some());
+
+    let rebuilt = Seeded::from_reflect(&*seeded.to_dynamic()).unwrap();
+    assert_eq!(rebuilt.map.hasher().0, 42);
+    assert_eq!(rebuilt.set.hasher().0, 42);
+    assert!(rebuilt.map.iter().eq(seeded.map.iter()));
+    assert!(rebuilt.set.iter().eq(seeded.set.iter()));
+
+    let cloned = seeded.reflect_clone().unwrap().take::<Seeded>().unwrap();
+    assert_eq!(cloned.map.hasher().0, 7);
+    assert_eq!(cloned.set.hasher().0, 7);
+    assert!(cloned.map.iter().eq(seeded.map.iter()));
+    assert!(cloned.set.iter().eq(seeded.set.iter()));
+}
+
+#[test]
+fn external_hasher_reflect() {
+    let mut map = IndexMap::with_hasher(ExternalState(3));
+    map.extend((0..5).rev().map(|i| (i, i * 10)));
+    let mut set = IndexSet::with_hasher(ExternalState(3));
+    set.extend((0..5).rev());
+
+    assert!(IndexMap::<u32, u32, ExternalState>::from_reflect(&map).is_none());
+    assert!(IndexSet::<u32, ExternalState>::from_reflect(&set).is_none());
+    assert!(matches!(
+        map.reflect_clone(),
+        Err(ReflectCloneError::NotCloneable { .. })
+    ));
+    assert!(matches!(
+        set.reflect_clone(),
+        Err(ReflectCloneError::NotCloneable { .. })
+    ));
+
+    let dynamic = map.to_dynamic();
+    let rebuilt =
+        IndexMap::<u32, u32, _>::from_reflect_with_hasher(&*dynamic, ExternalState(5)).unwrap();
+    assert_eq!(rebuilt.hasher().0, 5);
+    assert!(rebuilt.iter().eq(map.iter()));
+
+    let dynamic = set.to_dynamic();
+    let rebuilt =
+        IndexSet::<u32, _>::from_reflect_with_hasher(&*dynamic, ExternalState(5)).unwrap();
+    assert_eq!(rebuilt.hasher().0, 5);
+    assert!(rebuilt.iter().eq(set.iter()));
+
+    assert!(IndexSet::<u32, _>::from_reflect_with_hasher(&map, ExternalState(5)).is_none());
+}
+
+#[test]
+fn diff_apply_patch() {
+    let old: IndexMap<u32, u32> = (0..10).map(|i| (i, i)).collect();
+    let targets: [IndexMap<u32, u32>; 6] = [
+        old.clone(),
+        (0..10).rev().map(|i| (i, i)).collect(),
+        (5..15).map(|i| (i, i * 2)).collect(),
+        [(9, 9), (20, 20), (0, 0), (3, 30), (1, 1), (21, 21)]
+            .into_iter()
+            .collect(),
+        (0..10).map(|i| ((i + 3) % 10, (i + 3) % 10)).collect(),
+        IndexMap::new(),
+    ];
+
+    for new in &targets {
+        let patch = old.diff(new);
+        let mut map = old.clone();
+        map.apply_patch(&patch);
+        assert!(map.iter().eq(new), "{map:?} != {new:?}");
+
+        let mut none = new.clone();
+        none.apply_patch(&new.diff(new));
+        assert!(none.iter().eq(new));
+    }
+
+    assert!(old.diff(&targets[0]).is_empty());
+    // Only the keys that are out of order with the rest are moved.
+    assert_eq!(old.diff(&targets[1]).moved().len(), 9);
+    assert_eq!(old.diff(&targets[4]).moved().len(), 3);
+    assert!(old.diff(&targets[2]).moved().is_empty());
+}
+
+#[test]
+fn reflect_patch() {
+    let old: IndexMap<u32, String> = (0..5).map(|i| (i, i.to_string())).collect();
+    let mut new = old.clone();
+    new.shift_remove(&1);
+    new.insert(7, "seven".to_string());
+    new[&3] = "three".to_string();
+    new.move_index(0, 3);
+
+    let patch = old.diff(&new);
+    let mut registry = TypeRegistry::default();
+    registry.register::<IndexMapPatch<u32, String>>();
+    assert!(registry.contains(TypeId::of::<IndexMapPatch<u32, String>>()));
+    let reflected = patch.to_dynamic();
+    let patch = <IndexMapPatch<u32, String>>::from_reflect(&*reflected).unwrap();
+
+    let mut map = old.clone();
+    map.apply_patch(&patch);
+    assert!(map.iter().eq(&new));
+}
+
+#[test]
+fn reflected_diff_apply_patch() {
+    let mut registry = TypeRegistry::default();
+    registry.register::<IndexMap<u32, String>>();
+    let indexed = registry
+        .get_type_data::<ReflectIndexed>(TypeId::of::<IndexMap<u32, String>>())
+        .unwrap();
+
+    let old: IndexMap<u32, String> = (0..5).map(|i| (i, i.to_string())).collect();
+    let new: IndexMap<u32, String> = [(4, "4"), (7, "seven"), (0, "0"), (2, "two")]
+        .into_iter()
+        .map(|(key, value)| (key, value.to_string()))
+        .collect();
+
+    // The source map can be any reflected map.
+    let patch = indexed.diff(&old.to_dynamic_map(), &new).unwrap();
+    assert_eq!(patch.removed().len(), 2);
+    assert_eq!(patch.changed().len(), 1);
+    assert_eq!(patch.inserted()[0].0, 1);
+
+    let mut map = old.clone();
+    indexed.apply_patch(&mut map, &patch).unwrap();
+    assert!(map.iter().eq(&new));
+
+    let mut dynamic = old.to_dynamic_map();
+    assert!(matches!(
+        indexed.apply_patch(&mut dynamic, &patch),
+        Err(ReflectIndexedError::MismatchedType { .. })
+    ));
+    assert!(matches!(
+        indexed.diff(&new, &old.to_dynamic_map()),
+        Err(ReflectIndexedError::MismatchedType { .. })
+    ));
+}
+
+#[test]
+#[cfg(feature = "functions")]
+fn registered_functions() {
+    use alloc::format;
+    use bevy_reflect::func::{ArgList, FunctionRegistrationError, FunctionRegistry, Return};
+
+    type Map = IndexMap<u32, String>;
+    type Set = IndexSet<u32>;
+
+    fn call<'a>(
+        registry: &FunctionRegistry,
+        path: &str,
+        method: &str,
+        args: ArgList<'a>,
+    ) -> Return<'a> {
+        let name = format!("{path}::{method}");
+        registry.call(&name, args).unwrap().unwrap()
+    }
+    fn owned<T: FromReflect>(ret: Return<'_>) -> T {
+        T::from_reflect(&*ret.unwrap_owned()).unwrap()
+    }
+
+    let mut registry = FunctionRegistry::default();
+    Map::register_functions(&mut registry).unwrap();
+    Map::register_sort_functions(&mut registry).unwrap();
+    Set::register_functions(&mut registry).unwrap();
+    Set::register_sort_functions(&mut registry).unwrap();
+    assert!(matches!(
+        Map::register_functions(&mut registry),
+        Err(FunctionRegistrationError::DuplicateName(_))
+    ));
+
+    // A single taken name keeps every other function from being registered.
+    let mut partial = FunctionRegistry::default();
+    let reverse = format!("{}::reverse", Map::type_path());
+    partial
+        .register_with_name(reverse, |map: &mut Map| map.reverse())
+        .unwrap();
+    assert!(Map::register_functions(&mut partial).is_err());
+    assert_eq!(partial.len(), 1);
+
+    let path = Map::type_path();
+    let mut map = Map::new();
+    for (key, value) in [(3_u32, "c"), (1, "a"), (2, "b")] {
+        let args = ArgList::new()
+            .with_mut(&mut map)
+            .with_owned(key)
+            .with_owned(value.to_string());
+        assert_eq!(
+            owned::<Option<String>>(call(&registry, path, "insert", args)),
+            None
+        );
+    }
+    let args = ArgList::new()
+        .with_mut(&mut map)
+        .with_owned(0_usize)
+        .with_owned(2_usize);
+    assert_eq!(
+        owned::<Option<()>>(call(&registry, path, "move_index", args)),
+        Some(())
+    );
+    assert!(map.keys().eq(&[1, 2, 3]));
+    let args = ArgList::new()
+        .with_mut(&mut map)
+        .with_owned(0_usize)
+        .with_owned(3_usize);
+    assert_eq!(
+        owned::<Option<()>>(call(&registry, path, "move_index", args)),
+        None
+    );
+    let args = ArgList::new()
+        .with_mut(&mut map)
+        .with_owned(3_usize)
+        .with_owned(1_u32)
+        .with_owned("x".to_string());
+    assert_eq!(
+        owned::<Option<Option<String>>>(call(&registry, path, "shift_insert", args)),
+        None
+    );
+    let args = ArgList::new()
+        .with_mut(&mut map)
+        .with_owned(4_usize)
+        .with_owned(4_u32)
+        .with_owned("d".to_string());
+    assert_eq!(
+        owned::<Option<Option<String>>>(call(&registry, path, "shift_insert", args)),
+        None
+    );
+    let args = ArgList::new().with_mut(&mut map).with_owned(3_usize);
+    assert_eq!(
+        owned::<Option<(u32, String)>>(call(&registry, path, "shift_remove_index", args)),
+        None
+    );
+    assert!(map.keys().eq(&[1, 2, 3]));
+
+    let args = ArgList::new().with_ref(&map).with_owned(2_u32);
+    assert_eq!(
+        owned::<Option<String>>(call(&registry, path, "get", args)),
+        Some("b".to_string())
+    );
+    let args = ArgList::new().with_ref(&map).with_owned(0_usize);
+    assert_eq!(
+        owned::<Option<(u32, String)>>(call(&registry, path, "get_index", args)),
+        Some((1, "a".to_string()))
+    );
+    let args = ArgList::new().with_mut(&mut map).with_owned(1_u32);
+    assert_eq!(
+        owned::<Option<String>>(call(&registry, path, "shift_remove", args)),
+        Some("a".to_string())
+    );
+    call(
+        &registry,
+        path,
+        "reverse",
+        ArgList::new().with_mut(&mut map),
+    );
+    assert!(map.keys().eq(&[3, 2]));
+    call(
+        &registry,
+        path,
+        "sort_keys",
+        ArgList::new().with_mut(&mut map),
+    );
+    assert_eq!(
+        owned::<usize>(call(&registry, path, "len", ArgList::new().with_ref(&map))),
+        2
+    );
+    assert_eq!(
+        owned::<Vec<u32>>(call(&registry, path, "keys", ArgList::new().with_ref(&map))),
+        [2, 3]
+    );
+
+    let path = Set::type_path();
+    let mut set = Set::new();
+    for value in [5_u32, 4, 5, 6] {
+        call(
+            &registry,
+            path,
+            "insert",
+            ArgList::new().with_mut(&mut set).with_owned(value),
+        );
+    }
+    let args = ArgList::new()
+        .with_mut(&mut set)
+        .with_owned(0_usize)
+        .with_owned(2_usize);
+    assert_eq!(
+        owned::<Option<()>>(call(&registry, path, "swap_indices", args)),
+        Some(())
+    );
+    let args = ArgList::new()
+        .with_mut(&mut set)
+        .with_owned(0_usize)
+        .with_owned(3_usize);
+    assert_eq!(
+        owned::<Option<()>>(call(&registry, path, "swap_indices", args)),
+        None
+    );
+    let args = ArgList::new()
+        .with_mut(&mut set)
+        .with_owned(3_usize)
+        .with_owned(5_u32);
+    assert_eq!(
+        owned::<Option<bool>>(call(&registry, path, "shift_insert", args)),
+        None
+    );
+    assert_eq!(
+        owned::<Vec<u32>>(call(
+            &registry,
+            path,
+            "to_vec",
+            ArgList::new().with_ref(&set)
+        )),
+        [6, 4, 5]
+    );
+    let args = ArgList::new().with_ref(&set).with_owned(5_u32);
+    assert_eq!(
+        owned::<Option<usize>>(call(&registry, path, "get_index_of", args)),
+        Some(2)
+    );
+    call(&registry, path, "sort", ArgList::new().with_mut(&mut set));
+    assert_eq!(
+        owned::<Option<u32>>(call(
+            &registry,
+            path,
+            "first",
+            ArgList::new().with_ref(&set)
+        )),
+        Some(4)
+    );
+}
+
+// Conformance of the reflected `Map` and `Set` trait contracts, for each hasher.
+
+fn map_entries(map: &dyn Map) -> Vec<(u32, String)> {
+    map.iter()
+        .map(|(key, value)| {
+            let key = *key.try_downcast_ref::<u32>().unwrap();
+            (key, value.try_downcast_ref::<String>().unwrap().clone())
+        })
+        .collect()
+}
+
+fn boxed_entry(key: u32, value: &str) -> (Box<dyn PartialReflect>, Box<dyn PartialReflect>) {
+    (Box::new(key), Box::new(value.to_string()))
+}
+
+fn map_conformance<M: Map + Reflect>(new: impl Fn() -> M) {
+    let expect = |entries: &[(u32, &str)]| -> Vec<(u32, String)> {
+        entries.iter().map(|&(k, v)| (k, v.to_string())).collect()
+    };
+
+    let mut map = new();
+    assert!(map.drain().is_empty());
+    assert!(map.remove(&0u32).is_none());
+
+    // `insert_boxed` appends new keys, and replaces values in place.
+    for (key, value) in [(3, "c"), (1, "a"), (2, "b"), (5, "e")] {
+        let (key, value) = boxed_entry(key, value);
+        assert!(map.insert_boxed(key, value).is_none());
+    }
+    let (key, value) = boxed_entry(1, "A");
+    let old = map.insert_boxed(key, value).unwrap();
+    assert_eq!(old.try_downcast_ref::<String>().unwrap(), "a");
+    assert_eq!(
+        map_entries(&map),
+        expect(&[(3, "c"), (1, "A"), (2, "b"), (5, "e")])
+    );
+    assert_eq!(map.len(), 4);
+
+    // `remove` keeps the order of the other entries.
+    let removed = map.remove(&2u32).unwrap();
+    assert_eq!(removed.try_downcast_ref::<String>().unwrap(), "b");
+    assert!(map.remove(&2u32).is_none());
+    assert_eq!(map_entries(&map), expect(&[(3, "c"), (1, "A"), (5, "e")]));
+
+    let clone = map.reflect_clone().unwrap();
+    assert!(clone.is::<M>());
+    assert_eq!(
+        map_entries(clone.reflect_ref().as_map().unwrap()),
+        map_entries(&map)
+    );
+    let dynamic = map.to_dynamic_map();
+    assert_eq!(map_entries(&dynamic), map_entries(&map));
+
+    // Equality doesn't depend on the order, like for other maps.
+    let mut reordered = DynamicMap::default();
+    for (key, value) in [(5u32, "e"), (3, "c"), (1, "A")] {
+        reordered.insert_boxed(Box::new(key), Box::new(value.to_string()));
+    }
+    assert_eq!(map.reflect_partial_eq(&reordered), Some(true));
+    assert_eq!(map.reflect_partial_eq(&*clone), Some(true));
+    reordered.insert(1u32, "a".to_string());
+    assert_eq!(map.reflect_partial_eq(&reordered), Some(false));
+    reordered.insert(1u32, "A".to_string());
+    reordered.insert(7u32, "g".to_string());
+    assert_eq!(map.reflect_partial_eq(&reordered), Some(false));
+    assert_eq!(map.reflect_partial_eq(&3u32), Some(false));
+
+    // `apply` updates the values in place and appends the new keys.
+    let mut source = DynamicMap::default();
+    source.insert(7u32, "g".to_string());
+    source.insert(3u32, "C".to_string());
+    map.apply(&source);
+    assert_eq!(
+        map_entries(&map),
+        expect(&[(3, "C"), (1, "A"), (5, "e"), (7, "g")])
+    );
+    assert!(matches!(
+        map.try_apply(&3u32),
+        Err(ApplyError::MismatchedKinds { .. })
+    ));
+
+    // `drain` takes every entry, in order.
+    let drained: Vec<_> = map
+        .drain()
+        .into_iter()
+        .map(|(key, value)| {
+            let key = *key.try_downcast_ref::<u32>().unwrap();
+            (key, value.try_downcast_ref::<String>().unwrap().clone())
+        })
+        .collect();
+    assert_eq!(drained, expect(&[(3, "C"), (1, "A"), (5, "e"), (7, "g")]));
+    assert!(map.is_empty());
+    assert!(map.drain().is_empty());
+}
+
+fn set_values(set: &dyn Set) -> Vec<u32> {
+    set.iter()
+        .map(|value| *value.try_downcast_ref::<u32>().unwrap())
+        .collect()
+}
+
+fn set_conformance<T: Set + Reflect>(new: impl Fn() -> T) {
+    let mut set = new();
+    assert!(set.drain().is_empty());
+    assert!(!set.remove(&0u32));
+
+    // `insert_boxed` appends new values, and keeps existing values in place.
+    for value in [3u32, 1, 2, 5] {
+        assert!(set.insert_boxed(Box::new(value)));
+    }
+    assert!(!set.insert_boxed(Box::new(1u32)));
+    assert_eq!(set_values(&set), [3, 1, 2, 5]);
+    assert_eq!(set.len(), 4);
+
+    // `remove` keeps the order of the other values.
+    assert!(set.remove(&2u32));
+    assert!(!set.remove(&2u32));
+    assert!(set.contains(&5u32));
+    assert!(!set.contains(&2u32));
+    assert_eq!(set_values(&set), [3, 1, 5]);
+
+    let clone = set.reflect_clone().unwrap();
+    assert!(clone.is::<T>());
+    assert_eq!(set_values(clone.reflect_ref().as_set().unwrap()), [3, 1, 5]);
+    let dynamic = set.to_dynamic_set();
+    let mut dynamic_values = set_values(&dynamic);
+    dynamic_values.sort_unstable();
+    assert_eq!(dynamic_values, [1, 3, 5]);
+
+    // Equality doesn't depend on the order, like for other sets.
+    let mut reordered = bevy_reflect::DynamicSet::default();
+    for value in [5u32, 3, 1] {
+        reordered.insert(value);
+    }
+    assert_eq!(set.reflect_partial_eq(&reordered), Some(true));
+    assert_eq!(set.reflect_partial_eq(&*clone), Some(true));
+    reordered.insert(7u32);
+    assert_eq!(set.reflect_partial_eq(&reordered), Some(false));
+    assert_eq!(set.reflect_partial_eq(&3u32), Some(false));
+
+    // `apply` appends the new values.
+    let mut source = bevy_reflect::DynamicSet::default();
+    source.insert(7u32);
+    source.insert(3u32);
+    set.apply(&source);
+    assert_eq!(set_values(&set), [3, 1, 5, 7]);
+    assert!(matches!(
+        set.try_apply(&3u32),
+        Err(ApplyError::MismatchedKinds { .. })
+    ));
+
+    // `drain` takes every value, in order.
+    let drained: Vec<u32> = set
+        .drain()
+        .into_iter()
+        .map(|value| *value.try_downcast_ref::<u32>().unwrap())
+        .collect();
+    assert_eq!(drained, [3, 1, 5, 7]);
+    assert!(set.is_empty());
+    assert!(set.drain().is_empty());
+}
+
+#[test]
+fn map_conformance_default_hasher() {
+    map_conformance(IndexMap::<u32, String>::new);
+}
+
+#[test]
+fn map_conformance_seeded_hasher() {
+    map_conformance(|| IndexMap::<u32, String, _>::with_hasher(SeededState(11)));
+}
+
+#[test]
+fn set_conformance_default_hasher() {
+    set_conformance(IndexSet::<u32>::new);
+}
+
+#[test]
+fn set_conformance_seeded_hasher() {
+    set_conformance(|| IndexSet::<u32, _>::with_hasher(SeededState(11)));
+}
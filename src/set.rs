@@ -1,5 +1,6 @@
 //! A hash set implemented using [`IndexMap`]
 
+mod entry;
 mod iter;
 mod mutable;
 mod slice;
@@ -7,49 +8,12 @@ mod slice;
 #[cfg(test)]
 mod tests;
 
+pub use self::entry::{Entry, OccupiedEntry, VacantEntry};
 pub use self::iter::{
     Difference, Drain, Intersection, IntoIter, Iter, Splice, SymmetricDifference, Union,
 };
 pub use self::mutable::MutableValues;
 pub use self::slice::Slice;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::args::Arg;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::args::FromArg;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::args::GetOwnership;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::args::Ownership;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::ArgError;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::IntoReturn;
-#[cfg(feature = "functions")]
-use bevy_reflect::func::Return;
-use bevy_reflect::set_apply;
-use bevy_reflect::set_partial_eq;
-use bevy_reflect::set_try_apply;
-use bevy_reflect::ApplyError;
-use bevy_reflect::FromReflect;
-use bevy_reflect::FromType;
-use bevy_reflect::Generics;
-use bevy_reflect::GetTypeRegistration;
-use bevy_reflect::PartialReflect;
-use bevy_reflect::Reflect;
-use bevy_reflect::ReflectCloneError;
-use bevy_reflect::ReflectFromPtr;
-use bevy_reflect::ReflectFromReflect;
-use bevy_reflect::ReflectKind;
-use bevy_reflect::ReflectMut;
-use bevy_reflect::ReflectOwned;
-use bevy_reflect::ReflectRef;
-use bevy_reflect::Set;
-use bevy_reflect::SetInfo;
-use bevy_reflect::TypeInfo;
-use bevy_reflect::TypeParamInfo;
-use bevy_reflect::TypeRegistration;
-use bevy_reflect::TypeRegistry;
-use bevy_reflect::Typed;
 
 #[cfg(feature = "rayon")]
 pub use crate::rayon::set as rayon;
@@ -58,10 +22,6 @@ use crate::TryReserveError;
 #[cfg(feature = "std")]
 use std::collections::hash_map::RandomState;
 
-use super::reflect::impl_full_reflect;
-use super::reflect::impl_reflect_for_hashset;
-#[cfg(feature = "functions")]
-use super::reflect::{impl_from_arg, impl_function_traits, impl_get_ownership, impl_into_return};
 use crate::util::try_simplify_range;
 use alloc::boxed::Box;
 use alloc::vec::Vec;
@@ -71,14 +31,10 @@ use core::hash::{BuildHasher, Hash};
 use core::ops::{BitAnd, BitOr, BitXor, Index, RangeBounds, Sub};
 
 use super::{Entries, Equivalent, IndexMap};
-use crate::alloc::string::ToString;
-#[cfg(feature = "functions")]
-use crate::set::Return::Owned;
-use bevy_ecs::entity::{Entity, EntityHash, EntitySetIterator};
-use bevy_ecs::relationship::RelationshipSourceCollection;
-use bevy_reflect::utility::GenericTypeInfoCell;
-use bevy_reflect::TypePath;
-use std::borrow::Cow;
+#[cfg(feature = "bevy_ecs")]
+use bevy_ecs::entity::{Entity, EntityHash, EntityMapper, EntitySetIterator, MapEntities};
+#[cfg(feature = "bevy_ecs")]
+use bevy_ecs::relationship::{OrderedRelationshipSourceCollection, RelationshipSourceCollection};
 
 type Bucket<T> = super::Bucket<T, ()>;
 
@@ -131,12 +87,12 @@ type Bucket<T> = super::Bucket<T, ()>;
 /// assert!(!letters.contains(&'y'));
 /// ```
 #[cfg(feature = "std")]
-#[derive(TypePath)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::TypePath))]
 pub struct IndexSet<T, S = RandomState> {
     pub(crate) map: IndexMap<T, (), S>,
 }
 #[cfg(not(feature = "std"))]
-#[derive(TypePath)]
+#[cfg_attr(feature = "bevy_reflect", derive(bevy_reflect::TypePath))]
 pub struct IndexSet<T, S> {
     pub(crate) map: IndexMap<T, (), S>,
 }
@@ -543,6 +499,31 @@ where
         self.map.shift_insert(index, value, ()).is_none()
     }
 
+    /// Get the given value's corresponding entry in the set for insertion and/or
+    /// in-place manipulation.
+    ///
+    /// This finds the index of an existing value, or inserts a new one, with a single
+    /// hash lookup.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use indexmap::set::{Entry, IndexSet};
+    ///
+    /// let mut set: IndexSet<&str> = ["a", "b"].into();
+    /// match set.entry("c") {
+    ///     Entry::Occupied(entry) => panic!("unexpected {:?}", entry),
+    ///     Entry::Vacant(entry) => assert_eq!(entry.shift_insert(1).index(), 1),
+    /// }
+    /// assert_eq!(set.entry("b").index(), 2);
+    /// assert!(set.iter().eq(&["a", "c", "b"]));
+    /// ```
+    pub fn entry(&mut self, value: T) -> Entry<'_, T> {
+        self.map.entry(value).into()
+    }
+
     /// Adds a value to the set, replacing the existing value, if any, that is
     /// equal to the given one, without altering its insertion order. Returns
     /// the replaced value.
@@ -1354,37 +1335,144 @@ where
 
 // SAFETY: Difference stems from two correctly behaving `IndexSet<Entity, EntityHash>`s.
 
+#[cfg(feature = "bevy_ecs")]
 #[allow(unsafe_code)]
 unsafe impl EntitySetIterator for Difference<'_, Entity, EntityHash> {}
 
 // SAFETY: Intersection stems from two correctly behaving `IndexSet<Entity, EntityHash>`s.
 
+#[cfg(feature = "bevy_ecs")]
 #[allow(unsafe_code)]
 unsafe impl EntitySetIterator for Intersection<'_, Entity, EntityHash> {}
 
 // SAFETY: SymmetricDifference stems from two correctly behaving `IndexSet<Entity, EntityHash>`s.
 
+#[cfg(feature = "bevy_ecs")]
 #[allow(unsafe_code)]
 unsafe impl EntitySetIterator for SymmetricDifference<'_, Entity, EntityHash, EntityHash> {}
 
 // SAFETY: Union stems from two correctly behaving `IndexSet<Entity, EntityHash>`s.
 
+#[cfg(feature = "bevy_ecs")]
 #[allow(unsafe_code)]
 unsafe impl EntitySetIterator for Union<'_, Entity, EntityHash> {}
 
 // SAFETY: Splice stems from a correctly behaving `IndexSet<Entity, EntityHash>`s.
 
+#[cfg(feature = "bevy_ecs")]
 #[allow(unsafe_code)]
 unsafe impl<I: Iterator<Item = Entity>> EntitySetIterator for Splice<'_, I, Entity, EntityHash> {}
 
-impl_reflect_for_hashset!(IndexSet<V, S>);
-#[cfg(feature = "functions")]
-impl_function_traits!(IndexSet<V, S>;
-    <
+/// Maps every entity of the set, keeping their order.
+///
+/// If several entities are mapped to the same entity, only the first of them is kept.
+///
+/// Like for maps, `ReflectMapEntities` isn't registered for sets, since it would keep
+/// the unmapped entities: sets are mapped by the component that holds them.
+#[cfg(feature = "bevy_ecs")]
+impl<S> MapEntities for IndexSet<Entity, S>
+where
+    S: BuildHasher,
+{
+    fn map_entities<E: EntityMapper>(&mut self, entity_mapper: &mut E) {
+        // The mapped entities have new hashes, so the set is rebuilt in its original order.
+        let entities: Vec<Entity> = self.drain(..).collect();
+        self.extend(entities.into_iter().map(|e| entity_mapper.get_mapped(e)));
+    }
+}
+
+/// An ordered collection of relationship sources, without duplicates.
+///
+/// Entities are kept in the order they were added. Removing an entity
+/// [shifts][IndexSet::shift_remove] the following entities, which preserves
+/// that order but takes **O(n)** time.
+#[cfg(feature = "bevy_ecs")]
+impl RelationshipSourceCollection for IndexSet<Entity, EntityHash> {
+    type SourceIter<'a> = core::iter::Copied<Iter<'a, Entity>>;
+
+    fn new() -> Self {
+        IndexSet::with_hasher(EntityHash)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        IndexSet::with_capacity_and_hasher(capacity, EntityHash)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        IndexSet::reserve(self, additional);
+    }
 
-        V: Hash + Eq + FromReflect + TypePath + GetTypeRegistration,
+    fn add(&mut self, entity: Entity) -> bool {
+        self.insert(entity)
+    }
 
-        S: TypePath + BuildHasher + Default + Send + Sync
+    fn remove(&mut self, entity: Entity) -> bool {
+        self.shift_remove(&entity)
+    }
+
+    fn iter(&self) -> Self::SourceIter<'_> {
+        IndexSet::iter(self).copied()
+    }
 
-    >
-);
+    fn len(&self) -> usize {
+        IndexSet::len(self)
+    }
+
+    fn clear(&mut self) {
+        IndexSet::clear(self);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        IndexSet::shrink_to_fit(self);
+    }
+
+    fn extend_from_iter(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        self.extend(entities);
+    }
+}
+
+/// Positional access to ordered relationship sources.
+///
+/// The "stable" operations and the placement of existing entities use
+/// [`move_index`][IndexSet::move_index] and [`shift_remove_index`][IndexSet::shift_remove_index],
+/// so they never reorder the other entities.
+#[cfg(feature = "bevy_ecs")]
+impl OrderedRelationshipSourceCollection for IndexSet<Entity, EntityHash> {
+    fn insert(&mut self, index: usize, entity: Entity) {
+        let (from, _) = self.insert_full(entity);
+        self.swap_indices(from, index.min(self.len() - 1));
+    }
+
+    fn remove_at(&mut self, index: usize) -> Option<Entity> {
+        self.swap_remove_index(index)
+    }
+
+    fn insert_stable(&mut self, index: usize, entity: Entity) {
+        // An existing entity is moved before the entity at `index`, like a new one.
+        self.insert_before(index.min(self.len()), entity);
+    }
+
+    fn remove_at_stable(&mut self, index: usize) -> Option<Entity> {
+        self.shift_remove_index(index)
+    }
+
+    fn sort(&mut self) {
+        self.sort_unstable();
+    }
+
+    fn insert_sorted(&mut self, entity: Entity) {
+        IndexSet::insert_sorted(self, entity);
+    }
+
+    fn place_most_recent(&mut self, index: usize) {
+        if let Some(last) = self.len().checked_sub(1) {
+            self.move_index(last, index.min(last));
+        }
+    }
+
+    fn place(&mut self, entity: Entity, index: usize) {
+        if let Some(from) = self.get_index_of(&entity) {
+            self.move_index(from, index.min(self.len() - 1));
+        }
+    }
+}
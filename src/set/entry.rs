@@ -0,0 +1,213 @@
+use crate::map;
+use core::fmt;
+
+/// Entry for an existing value in an [`IndexSet`][crate::IndexSet]
+/// or a vacant location to insert one.
+///
+/// This `enum` is constructed from the [`entry`][crate::IndexSet::entry] method.
+pub enum Entry<'a, T> {
+    /// Existing slot with an equivalent value.
+    Occupied(OccupiedEntry<'a, T>),
+    /// Vacant slot (no equivalent value in the set).
+    Vacant(VacantEntry<'a, T>),
+}
+
+impl<'a, T> Entry<'a, T> {
+    /// Return the index where the value exists or will be inserted.
+    pub fn index(&self) -> usize {
+        match self {
+            Entry::Occupied(entry) => entry.index(),
+            Entry::Vacant(entry) => entry.index(),
+        }
+    }
+
+    /// Gets a reference to the entry's value, either within the set if occupied,
+    /// or else the new value that was used to find the entry.
+    pub fn get(&self) -> &T {
+        match self {
+            Entry::Occupied(entry) => entry.get(),
+            Entry::Vacant(entry) => entry.get(),
+        }
+    }
+
+    /// Inserts the entry's value at the end of the set if it is vacant, and returns an
+    /// `OccupiedEntry` either way.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn insert(self) -> OccupiedEntry<'a, T> {
+        match self {
+            Entry::Occupied(entry) => entry,
+            Entry::Vacant(entry) => entry.insert(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Entry<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("Entry");
+        match self {
+            Entry::Vacant(v) => tuple.field(v),
+            Entry::Occupied(o) => tuple.field(o),
+        };
+        tuple.finish()
+    }
+}
+
+/// A view into an occupied entry in an [`IndexSet`][crate::IndexSet].
+/// It is part of the [`Entry`] enum.
+pub struct OccupiedEntry<'a, T> {
+    inner: map::OccupiedEntry<'a, T, ()>,
+}
+
+impl<'a, T> OccupiedEntry<'a, T> {
+    /// Return the index of the value.
+    #[inline]
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Gets a reference to the entry's value in the set.
+    ///
+    /// Note that this is not the value that was used to find the entry. There may be an
+    /// observable difference if the value type has any distinguishing features outside of
+    /// `Hash` and `Eq`, like extra fields or the memory address of an allocation.
+    pub fn get(&self) -> &T {
+        self.inner.key()
+    }
+
+    /// Remove and return the value stored in the set for this entry.
+    ///
+    /// Like [`Vec::swap_remove`][alloc::vec::Vec::swap_remove], the value is removed by
+    /// swapping it with the last element of the set and popping it off.
+    /// **This perturbs the position of what used to be the last element!**
+    ///
+    /// Computes in **O(1)** time (average).
+    pub fn swap_remove(self) -> T {
+        self.inner.swap_remove_entry().0
+    }
+
+    /// Remove and return the value stored in the set for this entry.
+    ///
+    /// Like [`Vec::remove`][alloc::vec::Vec::remove], the value is removed by shifting all
+    /// of the elements that follow it, preserving their relative order.
+    /// **This perturbs the index of all of those elements!**
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn shift_remove(self) -> T {
+        self.inner.shift_remove_entry().0
+    }
+
+    /// Moves the position of the entry to a new index
+    /// by shifting all other entries in-between.
+    ///
+    /// This is equivalent to [`IndexSet::move_index`][`crate::IndexSet::move_index`]
+    /// coming `from` the current [`.index()`][Self::index].
+    ///
+    /// * If `self.index() < to`, the other values will shift down while the targeted value
+    ///   moves up.
+    /// * If `self.index() > to`, the other values will shift up while the targeted value
+    ///   moves down.
+    ///
+    /// ***Panics*** if `to` is out of bounds.
+    ///
+    /// Computes in **O(n)** time (average).
+    #[track_caller]
+    pub fn move_index(self, to: usize) {
+        self.inner.move_index(to);
+    }
+
+    /// Swaps the position of entry with another.
+    ///
+    /// This is equivalent to [`IndexSet::swap_indices`][`crate::IndexSet::swap_indices`]
+    /// with the current [`.index()`][Self::index] as one of the two being swapped.
+    ///
+    /// ***Panics*** if the `other` index is out of bounds.
+    ///
+    /// Computes in **O(1)** time (average).
+    #[track_caller]
+    pub fn swap_indices(self, other: usize) {
+        self.inner.swap_indices(other);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OccupiedEntry<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OccupiedEntry").field(self.get()).finish()
+    }
+}
+
+/// A view into a vacant entry in an [`IndexSet`][crate::IndexSet].
+/// It is part of the [`Entry`] enum.
+pub struct VacantEntry<'a, T> {
+    inner: map::VacantEntry<'a, T, ()>,
+}
+
+impl<'a, T> VacantEntry<'a, T> {
+    /// Return the index where the value may be inserted.
+    pub fn index(&self) -> usize {
+        self.inner.index()
+    }
+
+    /// Gets a reference to the value that was used to find the entry.
+    pub fn get(&self) -> &T {
+        self.inner.key()
+    }
+
+    /// Takes ownership of the value, leaving the entry vacant.
+    pub fn into_value(self) -> T {
+        self.inner.into_key()
+    }
+
+    /// Inserts the entry's value at the end of the set, and returns an `OccupiedEntry`.
+    ///
+    /// Computes in **O(1)** time (amortized average).
+    pub fn insert(self) -> OccupiedEntry<'a, T> {
+        OccupiedEntry {
+            inner: self.inner.insert_entry(()),
+        }
+    }
+
+    /// Inserts the entry's value into the set at its ordered position among sorted
+    /// values, and returns an `OccupiedEntry`.
+    ///
+    /// If the existing values are **not** already sorted, then the insertion
+    /// index is unspecified (like [`slice::binary_search`]), but the value
+    /// is inserted at that position regardless.
+    ///
+    /// Computes in **O(n)** time (average).
+    pub fn insert_sorted(self) -> OccupiedEntry<'a, T>
+    where
+        T: Ord,
+    {
+        let index = self.inner.sorted_index();
+        self.shift_insert(index)
+    }
+
+    /// Inserts the entry's value into the set at the given index,
+    /// shifting others to the right, and returns an `OccupiedEntry`.
+    ///
+    /// ***Panics*** if `index` is out of bounds.
+    ///
+    /// Computes in **O(n)** time (average).
+    #[track_caller]
+    pub fn shift_insert(self, index: usize) -> OccupiedEntry<'a, T> {
+        OccupiedEntry {
+            inner: self.inner.shift_insert_entry(index, ()),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for VacantEntry<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.get()).finish()
+    }
+}
+
+impl<'a, T> From<map::Entry<'a, T, ()>> for Entry<'a, T> {
+    fn from(entry: map::Entry<'a, T, ()>) -> Self {
+        match entry {
+            map::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry { inner }),
+            map::Entry::Vacant(inner) => Entry::Vacant(VacantEntry { inner }),
+        }
+    }
+}
@@ -721,3 +721,342 @@ fn test_partition_point() {
     assert_eq!(b.partition_point(|&x| x < 7), 2);
     assert_eq!(b.partition_point(|&x| x < 8), 3);
 }
+
+#[test]
+fn entry() {
+    let mut set: IndexSet<i32> = [10, 20, 30].into_iter().collect();
+
+    match set.entry(20) {
+        Entry::Occupied(entry) => {
+            assert_eq!(entry.index(), 1);
+            assert_eq!(*entry.get(), 20);
+        }
+        Entry::Vacant(_) => panic!("expected an occupied entry"),
+    }
+    let entry = set.entry(40);
+    assert_eq!(entry.index(), 3);
+    assert_eq!(*entry.get(), 40);
+    assert_eq!(entry.insert().index(), 3);
+    assert_eq!(set.entry(40).insert().index(), 3);
+    assert_eq!(set.len(), 4);
+
+    match set.entry(25) {
+        Entry::Vacant(entry) => {
+            let entry = entry.insert_sorted();
+            assert_eq!(entry.index(), 2);
+            entry.move_index(0);
+        }
+        Entry::Occupied(_) => panic!("expected a vacant entry"),
+    }
+    assert_eq!(set.as_slice(), &[25, 10, 20, 30, 40][..]);
+
+    match set.entry(5) {
+        Entry::Vacant(entry) => assert_eq!(entry.into_value(), 5),
+        Entry::Occupied(_) => panic!("expected a vacant entry"),
+    }
+    if let Entry::Vacant(entry) = set.entry(15) {
+        entry.shift_insert(1).swap_indices(4);
+    }
+    assert_eq!(set.as_slice(), &[25, 30, 10, 20, 15, 40][..]);
+
+    if let Entry::Occupied(entry) = set.entry(30) {
+        assert_eq!(entry.swap_remove(), 30);
+    }
+    assert_eq!(set.as_slice(), &[25, 40, 10, 20, 15][..]);
+    if let Entry::Occupied(entry) = set.entry(25) {
+        assert_eq!(entry.shift_remove(), 25);
+    }
+    assert_eq!(set.as_slice(), &[40, 10, 20, 15][..]);
+}
+
+#[cfg(feature = "bevy_ecs")]
+mod relationship {
+    use super::IndexSet;
+    use crate::ecs::OrderedRelated;
+    use bevy_ecs::component::Component;
+    use bevy_ecs::entity::{Entity, EntityHash};
+    use bevy_ecs::world::World;
+    use std::vec::Vec;
+
+    #[derive(Component)]
+    #[relationship(relationship_target = Members)]
+    struct MemberOf(Entity);
+
+    #[derive(Component)]
+    #[relationship_target(relationship = MemberOf)]
+    struct Members(IndexSet<Entity, EntityHash>);
+
+    #[derive(Component)]
+    struct Unrelated;
+
+    #[derive(Component)]
+    struct Weight(i32);
+
+    fn members(world: &World, group: Entity) -> Vec<Entity> {
+        world
+            .get::<Members>(group)
+            .map(|members| members.0.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn order_survives_despawns() {
+        let mut world = World::new();
+        let group = world.spawn_empty().id();
+
+        let mut expected = Vec::new();
+        let mut unrelated = Vec::new();
+        for _ in 0..5 {
+            unrelated.push(world.spawn(Unrelated).id());
+            expected.push(world.spawn(MemberOf(group)).id());
+        }
+        assert_eq!(members(&world, group), expected);
+
+        for entity in unrelated {
+            world.despawn(entity);
+        }
+        assert_eq!(members(&world, group), expected);
+
+        // Inserting the relationship again re-adds the entity at the back, only once.
+        world.entity_mut(expected[1]).insert(MemberOf(group));
+        let entity = expected.remove(1);
+        expected.push(entity);
+        assert_eq!(members(&world, group), expected);
+
+        world.despawn(expected.remove(2));
+        assert_eq!(members(&world, group), expected);
+
+        world.entity_mut(expected.remove(0)).remove::<MemberOf>();
+        assert_eq!(members(&world, group), expected);
+
+        let late = world.spawn(MemberOf(group)).id();
+        expected.push(late);
+        assert_eq!(members(&world, group), expected);
+
+        for entity in expected {
+            world.despawn(entity);
+        }
+        assert!(members(&world, group).is_empty());
+    }
+
+    #[test]
+    fn insert_related_places_entities() {
+        let mut world = World::new();
+        let group = world.spawn_empty().id();
+        let a = world.spawn(MemberOf(group)).id();
+        let b = world.spawn(MemberOf(group)).id();
+        let c = world.spawn(MemberOf(group)).id();
+        let d = world.spawn_empty().id();
+
+        // Existing entities are moved without reordering the others.
+        world
+            .entity_mut(group)
+            .insert_related::<MemberOf>(0, &[c, d]);
+        assert_eq!(members(&world, group), [c, d, a, b]);
+        world.entity_mut(group).insert_related::<MemberOf>(9, &[c]);
+        assert_eq!(members(&world, group), [d, a, b, c]);
+    }
+
+    #[test]
+    fn ordered_related_methods() {
+        let mut world = World::new();
+        let group = world.spawn_empty().id();
+        let a = world.spawn((MemberOf(group), Weight(3))).id();
+        let b = world.spawn((MemberOf(group), Weight(1))).id();
+        let c = world.spawn(MemberOf(group)).id();
+        let other = world.spawn_empty().id();
+        let d = world.spawn((MemberOf(other), Weight(2))).id();
+        let e = world.spawn(Weight(0)).id();
+
+        let mut group_mut = world.entity_mut(group);
+        group_mut.insert_related_before::<MemberOf>(b, &[d, e]);
+        assert_eq!(members(&world, group), [a, d, e, b, c]);
+        assert!(members(&world, other).is_empty());
+
+        let mut group_mut = world.entity_mut(group);
+        group_mut.insert_related_after::<MemberOf>(c, &[a, d]);
+        assert_eq!(members(&world, group), [e, b, c, a, d]);
+
+        let mut group_mut = world.entity_mut(group);
+        group_mut
+            .move_related_index::<MemberOf>(4, 0)
+            .swap_related_indices::<MemberOf>(1, 2)
+            .move_related_index::<MemberOf>(9, 0)
+            .swap_related_indices::<MemberOf>(0, 9);
+        assert_eq!(members(&world, group), [d, b, e, c, a]);
+
+        let mut commands = world.commands();
+        commands
+            .entity(group)
+            .sort_related_by_key::<MemberOf, Weight, _, _>(|weight| weight.0);
+        world.flush();
+        assert_eq!(members(&world, group), [e, b, d, a, c]);
+
+        let mut commands = world.commands();
+        commands
+            .entity(group)
+            .insert_related_after::<MemberOf>(other, &[c])
+            .move_related_index::<MemberOf>(0, 9)
+            .swap_related_indices::<MemberOf>(0, 1);
+        world.flush();
+        assert_eq!(members(&world, group), [d, b, a, c, e]);
+    }
+
+    #[test]
+    fn ordered_source_collection() {
+        use bevy_ecs::relationship::OrderedRelationshipSourceCollection as Ordered;
+
+        let [a, b, c, d, e] = [1, 2, 3, 4, 5].map(Entity::from_raw);
+        let mut set: IndexSet<Entity, EntityHash> = [a, c, e].into_iter().collect();
+
+        Ordered::insert_sorted(&mut set, d);
+        Ordered::insert_sorted(&mut set, b);
+        assert!(set.iter().eq(&[a, b, c, d, e]));
+        // Sorted entities stay in place, instead of being moved after themselves.
+        Ordered::insert_sorted(&mut set, c);
+        Ordered::insert_sorted(&mut set, a);
+        Ordered::insert_sorted(&mut set, e);
+        assert!(set.iter().eq(&[a, b, c, d, e]));
+
+        Ordered::insert_stable(&mut set, 1, e);
+        assert!(set.iter().eq(&[a, e, b, c, d]));
+        Ordered::insert_stable(&mut set, 4, a);
+        assert!(set.iter().eq(&[e, b, c, a, d]));
+        Ordered::insert_stable(&mut set, 99, b);
+        assert!(set.iter().eq(&[e, c, a, d, b]));
+        assert_eq!(Ordered::remove_at_stable(&mut set, 1), Some(c));
+        Ordered::insert_stable(&mut set, 0, c);
+        assert!(set.iter().eq(&[c, e, a, d, b]));
+
+        Ordered::insert(&mut set, 0, d);
+        assert!(set.iter().eq(&[d, e, a, c, b]));
+        assert_eq!(Ordered::remove_at(&mut set, 1), Some(e));
+        assert!(set.iter().eq(&[d, b, a, c]));
+        Ordered::place(&mut set, a, 99);
+        Ordered::place(&mut set, e, 0);
+        assert!(set.iter().eq(&[d, b, c, a]));
+        Ordered::sort(&mut set);
+        assert!(set.iter().eq(&[a, b, c, d]));
+    }
+
+    #[test]
+    fn entity_index_collections_are_entity_sets() {
+        use crate::ecs::{EntityIndexMap, EntityIndexSet};
+        use bevy_ecs::entity::EntitySetIterator;
+        use bevy_ecs::system::Query;
+
+        let mut world = World::new();
+        let entities: Vec<Entity> = (0..5).map(|i| world.spawn(Weight(i)).id()).collect();
+
+        let set: EntityIndexSet = entities.iter().rev().copied().collect();
+        let map: EntityIndexMap<usize> = set.iter().map(|&e| (e, 0)).collect();
+        let keys: EntityIndexSet = map.keys().copied().collect_set();
+        assert_eq!(keys, set);
+
+        // Mutations go through methods that keep the entities unique.
+        let mut sorted = set.clone();
+        sorted.sort();
+        assert!(sorted.iter().eq(&entities));
+        assert!(!sorted.insert(entities[0]));
+        sorted.swap_indices(0, 4);
+        sorted.move_index(1, 3);
+        assert!(sorted.shift_remove(&entities[2]));
+        assert!(sorted
+            .iter()
+            .eq(&[entities[4], entities[3], entities[1], entities[0]]));
+        let mut sorted_map: EntityIndexMap<usize> = sorted.iter().map(|&e| (e, 0)).collect();
+        *sorted_map.get_mut(&entities[1]).unwrap() = 1;
+        sorted_map.retain(|_, v| *v == 0);
+        sorted_map.sort_keys();
+        assert!(sorted_map
+            .keys()
+            .eq(&[entities[0], entities[3], entities[4]]));
+
+        let mut query = world.query::<&mut Weight>();
+        let mut state = query.query_mut(&mut world);
+        add_weights(&mut state, set.get_range(1..4).unwrap(), 10);
+        add_weights(&mut state, map.keys(), 100);
+        add_weights(&mut state, set.clone().drain(3..), 1000);
+        let weights: Vec<i32> = entities
+            .iter()
+            .map(|&e| world.get::<Weight>(e).unwrap().0)
+            .collect();
+        assert_eq!(weights, [1100, 1111, 112, 113, 104]);
+
+        fn add_weights(
+            query: &mut Query<'_, '_, &mut Weight>,
+            entities: impl bevy_ecs::entity::EntitySet,
+            n: i32,
+        ) {
+            for mut weight in query.iter_many_unique_mut(entities) {
+                weight.0 += n;
+            }
+        }
+    }
+
+    #[test]
+    fn unique_entity_slices() {
+        use crate::ecs::{EntityIndexMap, EntityIndexSet};
+        use bevy_ecs::entity::EntitySetIterator;
+
+        let mut world = World::new();
+        let entities: Vec<Entity> = (0..7).map(|i| world.spawn(Weight(i)).id()).collect();
+        let set: EntityIndexSet = entities.iter().rev().copied().collect();
+        let map: EntityIndexMap<i32> = entities
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| (e, i as i32))
+            .collect();
+
+        let slice = set.as_slice();
+        assert!(slice.iter().eq(set.iter()));
+        assert_eq!(slice.as_set_slice(), IndexSet::as_slice(&set));
+        let chunks: Vec<Vec<Entity>> = slice
+            .chunks(3)
+            .map(|chunk| chunk.iter().copied().collect())
+            .collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.concat(), set.iter().copied().collect::<Vec<_>>());
+        assert_eq!(slice.chunks(3).next_back().unwrap().len(), 1);
+
+        let (left, right) = slice.split_at(4);
+        assert_eq!((left.len(), right.len()), (4, 3));
+        assert_eq!(right.first(), Some(&entities[2]));
+        assert_eq!(right.split_last().unwrap().0, &entities[0]);
+        assert!(slice.get_range(5..9).is_none());
+        assert_eq!(set.get_range(5..).unwrap(), right.get_range(1..).unwrap());
+
+        let mut query = world.query::<&mut Weight>();
+        let mut weights = query.query_mut(&mut world);
+        for (i, chunk) in map.as_unique_entity_slice().chunks(2).enumerate() {
+            for mut weight in weights.iter_many_unique_mut(chunk) {
+                weight.0 += 10 * i as i32;
+            }
+        }
+        let keys = map.get_unique_entity_range(1..4).unwrap();
+        assert_eq!(
+            keys.as_map_slice().values().copied().collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        let [mut a, b, c] = weights
+            .get_many_unique_mut(keys.to_unique_array().unwrap())
+            .unwrap();
+        a.0 += b.0 + c.0;
+        assert!(keys.to_unique_array::<2>().is_none());
+        assert_eq!(
+            slice
+                .iter()
+                .rev()
+                .copied()
+                .collect_set::<EntityIndexSet>()
+                .len(),
+            7
+        );
+
+        let weights: Vec<i32> = entities
+            .iter()
+            .map(|&e| world.get::<Weight>(e).unwrap().0)
+            .collect();
+        assert_eq!(weights, [0, 26, 12, 13, 24, 25, 36]);
+    }
+}
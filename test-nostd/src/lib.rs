@@ -3,10 +3,13 @@
 use core::hash::BuildHasherDefault;
 use core::hash::Hasher;
 
+use bevy_reflect::FromReflect;
+use bevy_reflect::PartialReflect;
+use bevy_reflect::TypePath;
 use indexmap::IndexMap;
 use indexmap::IndexSet;
 
-#[derive(Default)]
+#[derive(Default, TypePath)]
 struct BadHasher(u64);
 
 impl Hasher for BadHasher {
@@ -34,3 +37,24 @@ pub fn test_compile() {
     let mut set = Set::default();
     set.insert("a");
 }
+
+pub fn test_reflect() {
+    let mut map = Map::default();
+    map.insert(3u32, 9u64);
+    map.insert(1, 1);
+    map.insert(2, 4);
+
+    let dynamic = map.to_dynamic();
+    let from_dynamic = Map::<u32, u64>::from_reflect(&*dynamic).unwrap();
+    assert!(from_dynamic.iter().eq(map.iter()));
+
+    let cloned = map.reflect_clone().unwrap();
+    let cloned = cloned.take::<Map<u32, u64>>().unwrap();
+    assert!(cloned.iter().eq(map.iter()));
+
+    let mut set = Set::default();
+    set.insert(2u32);
+    set.insert(1);
+    let from_set = Set::<u32>::from_reflect(&set).unwrap();
+    assert!(from_set.iter().eq(set.iter()));
+}
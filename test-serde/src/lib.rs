@@ -1,7 +1,19 @@
 #![cfg(test)]
 
+use bevy_ecs::entity::hash_map::EntityHashMap;
+use bevy_ecs::entity::EntityHash;
+use bevy_ecs::prelude::*;
+use bevy_ecs::reflect::{AppTypeRegistry, ReflectMapEntities};
+use bevy_reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer};
+use bevy_reflect::std_traits::ReflectDefault;
+use bevy_reflect::{Reflect, ReflectSerialize, TypeRegistry};
+use bevy_scene::serde::SceneDeserializer;
+use bevy_scene::{ron, DynamicSceneBuilder};
+use core::any::TypeId;
 use fnv::FnvBuildHasher;
+use indexmap::ecs::EntityIndexMap;
 use indexmap::{indexmap, indexset, IndexMap, IndexSet};
+use serde::de::DeserializeSeed;
 use serde::{Deserialize, Serialize};
 use serde_test::{assert_tokens, Token};
 
@@ -113,3 +125,147 @@ fn test_serde_seq_map() {
         ],
     );
 }
+
+#[test]
+fn test_reflect_serde_map() {
+    let mut registry = TypeRegistry::default();
+    IndexMap::<String, u32>::register_reflect_serde(&mut registry);
+
+    let map: IndexMap<String, u32> = [("b", 2), ("c", 3), ("a", 1)]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    let json = serde_json::to_string(&TypedReflectSerializer::new(&map, &registry)).unwrap();
+    assert_eq!(json, r#"{"b":2,"c":3,"a":1}"#);
+
+    let registration = registry.get(TypeId::of::<IndexMap<String, u32>>()).unwrap();
+    let reflected = TypedReflectDeserializer::new(registration, &registry)
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+    // `ReflectDeserialize` produces the concrete type rather than a dynamic map.
+    let deserialized = reflected.try_take::<IndexMap<String, u32>>().unwrap();
+    assert!(deserialized.iter().eq(&map));
+
+    let default = registration.data::<ReflectDefault>().unwrap().default();
+    assert!(default.take::<IndexMap<String, u32>>().unwrap().is_empty());
+}
+
+#[test]
+fn test_reflect_serde_set() {
+    let mut registry = TypeRegistry::default();
+    IndexSet::<i32>::register_reflect_serde(&mut registry);
+
+    let set = indexset! { 3, -1, 2, 0 };
+    let json = serde_json::to_string(&TypedReflectSerializer::new(&set, &registry)).unwrap();
+    assert_eq!(json, "[3,-1,2,0]");
+
+    let registration = registry.get(TypeId::of::<IndexSet<i32>>()).unwrap();
+    let reflected = TypedReflectDeserializer::new(registration, &registry)
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .unwrap();
+    let deserialized = reflected.try_take::<IndexSet<i32>>().unwrap();
+    assert!(deserialized.iter().eq(&set));
+}
+
+#[derive(Reflect)]
+struct NotSerde(u32);
+
+#[test]
+fn test_reflect_without_serde() {
+    // With the `serde` feature on, collections of types that can't be (de)serialized
+    // can still be registered, just without the serde type data.
+    let mut registry = TypeRegistry::default();
+    registry.register::<IndexMap<u32, NotSerde>>();
+    registry.register::<IndexMap<String, u32>>();
+
+    let registration = registry
+        .get(TypeId::of::<IndexMap<u32, NotSerde>>())
+        .unwrap();
+    assert!(registration.data::<ReflectDefault>().is_some());
+    let registration = registry.get(TypeId::of::<IndexMap<String, u32>>()).unwrap();
+    assert!(registration.data::<ReflectSerialize>().is_none());
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Inventory {
+    #[entities]
+    items: IndexSet<Entity, EntityHash>,
+    #[entities]
+    owners: IndexMap<Entity, Entity, EntityHash>,
+    #[entities]
+    scores: EntityIndexMap<Option<Entity>>,
+}
+
+#[test]
+fn test_scene_map_entities() {
+    let registry = AppTypeRegistry::default();
+    registry.write().register::<Inventory>();
+    // Without this, sets go through `DynamicSet`, which doesn't keep their order.
+    IndexSet::<Entity, EntityHash>::register_reflect_serde(&mut registry.write());
+
+    let mut world = World::new();
+    world.insert_resource(registry.clone());
+    let entities: Vec<Entity> = (0..8).map(|_| world.spawn_empty().id()).collect();
+    // Neither order matches the order of the entities, nor of their hashes.
+    let items: IndexSet<Entity, EntityHash> = [5, 1, 7, 2].iter().map(|&i| entities[i]).collect();
+    let owners: IndexMap<Entity, Entity, EntityHash> = [(6, 0), (3, 4), (0, 6)]
+        .iter()
+        .map(|&(k, v)| (entities[k], entities[v]))
+        .collect();
+    let scores: EntityIndexMap<Option<Entity>> = [(4, None), (2, Some(7)), (6, Some(4))]
+        .iter()
+        .map(|&(k, v)| (entities[k], v.map(|v| entities[v])))
+        .collect();
+    world.entity_mut(entities[0]).insert(Inventory {
+        items: items.clone(),
+        owners: owners.clone(),
+        scores: scores.clone(),
+    });
+
+    let scene = DynamicSceneBuilder::from_world(&world)
+        .extract_entities(entities.iter().copied())
+        .build();
+    let ron = scene.serialize(&registry.read()).unwrap();
+
+    let mut reloaded = World::new();
+    reloaded.insert_resource(registry.clone());
+    // Spawn some entities first, so that the scene's entities get different ids.
+    for _ in 0..5 {
+        reloaded.spawn_empty();
+    }
+    let scene = SceneDeserializer {
+        type_registry: &registry.read(),
+    }
+    .deserialize(&mut ron::de::Deserializer::from_str(&ron).unwrap())
+    .unwrap();
+    let mut entity_map = EntityHashMap::default();
+    scene
+        .write_to_world(&mut reloaded, &mut entity_map)
+        .unwrap();
+
+    let mapped = |entity: &Entity| entity_map[entity];
+    assert!(entities.iter().all(|entity| mapped(entity) != *entity));
+
+    let inventory = reloaded.get::<Inventory>(mapped(&entities[0])).unwrap();
+    assert!(inventory
+        .items
+        .iter()
+        .eq(&items.iter().map(mapped).collect::<Vec<_>>()));
+    assert!(inventory
+        .owners
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .eq(owners.iter().map(|(k, v)| (mapped(k), mapped(v)))));
+    assert!(inventory.scores.iter().map(|(k, v)| (*k, *v)).eq(scores
+        .iter()
+        .map(|(k, v)| (mapped(k), v.as_ref().map(mapped)))));
+
+    // The collections are mapped by the `MapEntities` of the component, which replaces
+    // their entities. They have no `ReflectMapEntities`, since it would apply the mapped
+    // copy with the merge of `Map::apply` and keep the unmapped entities too.
+    let registry = registry.read();
+    assert!(registry
+        .get_type_data::<ReflectMapEntities>(TypeId::of::<IndexSet<Entity, EntityHash>>())
+        .is_none());
+}